@@ -0,0 +1,790 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use orbclient::Renderer;
+use orbfont::Font;
+use orbutils_shared::notification::Notification;
+
+use crate::config::config_lines;
+use crate::keymap::{set_keymap, Keymaps};
+use crate::locale::translate;
+use crate::placement::Rect;
+use crate::theme::{TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
+
+/// The config file holding applet settings
+const CONFIG_NAME: &str = "applets";
+
+/// The applets shown when none are configured, in order along the bar
+///
+/// The system applets are only shown once their path is configured.
+const DEFAULT_APPLETS: &[&str] = &[
+    "cpu",
+    "memory",
+    "battery",
+    "network",
+    "keyboard",
+    "notifications",
+];
+
+/// The applets that read a system file in the format used by Linux
+///
+/// Redox has no such files yet, so these have no default path and are hidden until `<name>_path`
+/// is configured.
+const SYSTEM_APPLETS: &[&str] = &["cpu", "memory", "battery", "network"];
+
+/// The most notifications kept in the history
+const HISTORY_LEN: usize = 50;
+
+/// How often applets are updated when no interval is configured
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What happens when an applet is clicked
+#[derive(Clone, Debug, PartialEq)]
+pub enum AppletAction {
+    /// Launch an exec string, like the Exec key of a desktop entry
+    Exec(String),
+    /// Show a dropdown menu with these entries, the chosen one is passed to `menu_chosen`
+    Menu(Vec<String>),
+}
+
+/// A status applet, shown on the bar before the clock
+///
+/// By default an applet draws a short label above its value, and is hidden while it has no
+/// value, for example when the system file it reads does not exist.
+pub trait Applet {
+    /// The short label drawn above the value, like `CPU`
+    fn label(&self) -> String;
+
+    /// The text of the current value, or None if it is unknown
+    fn value(&self) -> Option<String>;
+
+    /// How often `update` is called
+    fn update_interval(&self) -> Duration;
+
+    /// Read the current value from the system
+    fn update(&mut self);
+
+    /// Handle a click, returns what the bar should do
+    fn click(&mut self) -> Option<AppletAction> {
+        None
+    }
+
+    /// Handle an entry chosen from the menu returned by `click`, returns true if the value changed
+    fn menu_chosen(&mut self, _entry: &str) -> bool {
+        false
+    }
+
+    /// Handle a notification received by the launcher, returns true if the value changed
+    fn notify(&mut self, _notification: &Notification) -> bool {
+        false
+    }
+
+    /// The width of the applet on a horizontal bar, 0 if it is hidden
+    fn width(&self, font: &Font, font_size: i32) -> u32 {
+        let Some(value) = self.value() else {
+            return 0;
+        };
+        let label_w = font.render(&self.label(), font_size as f32).width();
+        let value_w = font.render(&value, font_size as f32).width();
+        label_w.max(value_w) + 8
+    }
+
+    /// The height of the applet on a vertical bar, 0 if it is hidden
+    fn height(&self, font_size: i32) -> u32 {
+        match self.value() {
+            Some(_) => 2 * font_size as u32 + 8,
+            None => 0,
+        }
+    }
+
+    /// Draw the applet centered in a rectangle of the bar
+    fn draw(&self, renderer: &mut dyn Renderer, font: &Font, font_size: i32, rect: Rect) {
+        let Some(value) = self.value() else {
+            return;
+        };
+        let (x, y, w, h) = rect;
+        let label = font.render(&self.label(), font_size as f32);
+        let value = font.render(&value, font_size as f32);
+        let mut y = y + (h as i32 - (label.height() + value.height()) as i32) / 2;
+        label.draw(
+            renderer,
+            x + (w as i32 - label.width() as i32) / 2,
+            y,
+            TEXT_COLOR,
+        );
+        y += label.height() as i32;
+        value.draw(
+            renderer,
+            x + (w as i32 - value.width() as i32) / 2,
+            y,
+            TEXT_HIGHLIGHT_COLOR,
+        );
+    }
+}
+
+/// The settings of one applet, from the `<name>_path`, `<name>_click` and `<name>_interval` keys
+/// of `launcher/applets`
+#[derive(Clone, Debug, Default)]
+pub struct AppletConfig {
+    /// The system file or directory the applet reads
+    pub path: Option<PathBuf>,
+    /// The exec string launched when the applet is clicked
+    pub click: Option<String>,
+    /// How often the applet is updated
+    pub interval: Option<Duration>,
+}
+
+impl AppletConfig {
+    fn interval(&self) -> Duration {
+        self.interval.unwrap_or(DEFAULT_INTERVAL)
+    }
+}
+
+/// Get the busy and total time of all CPUs from the `cpu` line of `/proc/stat`
+pub fn parse_cpu_times(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    // Guest time is already counted in user time, so only the first eight fields are summed
+    let times: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+    let total: u64 = times.iter().sum();
+    Some((total - idle, total))
+}
+
+/// Get the CPU load in percent between two samples of busy and total time
+pub fn cpu_load(prev: (u64, u64), now: (u64, u64)) -> Option<u32> {
+    let total = now.1.checked_sub(prev.1).filter(|total| *total > 0)?;
+    let busy = now.0.saturating_sub(prev.0).min(total);
+    Some((busy * 100 / total) as u32)
+}
+
+/// The CPU load since the last update, from a file like `/proc/stat` on Linux
+pub struct CpuApplet {
+    config: AppletConfig,
+    path: PathBuf,
+    /// The busy and total time at the last update, zero to show the load since boot first
+    times: (u64, u64),
+    load: Option<u32>,
+}
+
+impl CpuApplet {
+    pub fn new(config: AppletConfig) -> Self {
+        CpuApplet {
+            path: config.path.clone().unwrap_or_default(),
+            config,
+            times: (0, 0),
+            load: None,
+        }
+    }
+}
+
+impl Applet for CpuApplet {
+    fn label(&self) -> String {
+        translate("CPU")
+    }
+
+    fn value(&self) -> Option<String> {
+        self.load.map(|load| format!("{}%", load))
+    }
+
+    fn update_interval(&self) -> Duration {
+        self.config.interval()
+    }
+
+    fn update(&mut self) {
+        let Some(times) = read_to_string(&self.path).and_then(|x| parse_cpu_times(&x)) else {
+            self.load = None;
+            return;
+        };
+        if let Some(load) = cpu_load(self.times, times) {
+            self.load = Some(load);
+        }
+        self.times = times;
+    }
+
+    fn click(&mut self) -> Option<AppletAction> {
+        self.config.click.clone().map(AppletAction::Exec)
+    }
+}
+
+/// Get the memory in use in percent from `/proc/meminfo`
+pub fn parse_memory_use(meminfo: &str) -> Option<u32> {
+    let mut fields = BTreeMap::new();
+    for line in meminfo.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let kib = value.trim().trim_end_matches("kB").trim();
+            if let Ok(kib) = kib.parse::<u64>() {
+                fields.insert(key.trim(), kib);
+            }
+        }
+    }
+
+    let total = *fields.get("MemTotal").filter(|total| **total > 0)?;
+    // Older kernels do not estimate available memory
+    let available = match fields.get("MemAvailable") {
+        Some(available) => *available,
+        None => ["MemFree", "Buffers", "Cached"]
+            .iter()
+            .filter_map(|key| fields.get(key))
+            .sum(),
+    };
+    Some((total.saturating_sub(available) * 100 / total) as u32)
+}
+
+/// The memory in use, from a file like `/proc/meminfo` on Linux
+pub struct MemoryApplet {
+    config: AppletConfig,
+    path: PathBuf,
+    used: Option<u32>,
+}
+
+impl MemoryApplet {
+    pub fn new(config: AppletConfig) -> Self {
+        MemoryApplet {
+            path: config.path.clone().unwrap_or_default(),
+            config,
+            used: None,
+        }
+    }
+}
+
+impl Applet for MemoryApplet {
+    fn label(&self) -> String {
+        translate("MEM")
+    }
+
+    fn value(&self) -> Option<String> {
+        self.used.map(|used| format!("{}%", used))
+    }
+
+    fn update_interval(&self) -> Duration {
+        self.config.interval()
+    }
+
+    fn update(&mut self) {
+        self.used = read_to_string(&self.path).and_then(|x| parse_memory_use(&x));
+    }
+
+    fn click(&mut self) -> Option<AppletAction> {
+        self.config.click.clone().map(AppletAction::Exec)
+    }
+}
+
+/// The charge of a battery, from a power supply directory like
+/// `/sys/class/power_supply/BAT0` on Linux, with `capacity` and `status` files
+pub struct BatteryApplet {
+    config: AppletConfig,
+    path: PathBuf,
+    capacity: Option<u32>,
+    charging: bool,
+}
+
+impl BatteryApplet {
+    pub fn new(config: AppletConfig) -> Self {
+        BatteryApplet {
+            path: config.path.clone().unwrap_or_default(),
+            config,
+            capacity: None,
+            charging: false,
+        }
+    }
+}
+
+impl Applet for BatteryApplet {
+    fn label(&self) -> String {
+        translate("BAT")
+    }
+
+    /// The charge in percent, followed by `+` while charging
+    fn value(&self) -> Option<String> {
+        self.capacity
+            .map(|capacity| format!("{}%{}", capacity, if self.charging { "+" } else { "" }))
+    }
+
+    fn update_interval(&self) -> Duration {
+        self.config.interval()
+    }
+
+    fn update(&mut self) {
+        self.capacity = read_to_string(&self.path.join("capacity"))
+            .and_then(|x| x.trim().parse::<u32>().ok())
+            .map(|capacity| capacity.min(100));
+        self.charging = read_to_string(&self.path.join("status"))
+            .is_some_and(|status| status.trim() == "Charging");
+    }
+
+    fn click(&mut self) -> Option<AppletAction> {
+        self.config.click.clone().map(AppletAction::Exec)
+    }
+}
+
+/// Check if any network interface except loopback is up, from a directory like
+/// `/sys/class/net` with an `operstate` file for each interface
+///
+/// Returns None if there are no interfaces except loopback.
+pub fn network_up(path: &Path) -> Option<bool> {
+    let mut up_opt = None;
+    for entry_res in fs::read_dir(path).ok()? {
+        let Ok(entry) = entry_res else {
+            continue;
+        };
+        if entry.file_name() == "lo" {
+            continue;
+        }
+        let up = read_to_string(&entry.path().join("operstate"))
+            .is_some_and(|state| state.trim() == "up");
+        up_opt = Some(up_opt.unwrap_or(false) || up);
+    }
+    up_opt
+}
+
+/// Whether the network is connected, from a directory like `/sys/class/net` on Linux
+pub struct NetworkApplet {
+    config: AppletConfig,
+    path: PathBuf,
+    up: Option<bool>,
+}
+
+impl NetworkApplet {
+    pub fn new(config: AppletConfig) -> Self {
+        NetworkApplet {
+            path: config.path.clone().unwrap_or_default(),
+            config,
+            up: None,
+        }
+    }
+}
+
+impl Applet for NetworkApplet {
+    fn label(&self) -> String {
+        translate("NET")
+    }
+
+    fn value(&self) -> Option<String> {
+        self.up.map(|up| translate(if up { "up" } else { "down" }))
+    }
+
+    fn update_interval(&self) -> Duration {
+        self.config.interval()
+    }
+
+    fn update(&mut self) {
+        self.up = network_up(&self.path);
+    }
+
+    fn click(&mut self) -> Option<AppletAction> {
+        self.config.click.clone().map(AppletAction::Exec)
+    }
+}
+
+/// The active keyboard layout, with a menu of the keymaps known to inputd
+pub struct KeyboardApplet {
+    config: AppletConfig,
+    keymaps: Keymaps,
+}
+
+impl KeyboardApplet {
+    pub fn new(config: AppletConfig, keymaps: Keymaps) -> Self {
+        KeyboardApplet { config, keymaps }
+    }
+}
+
+impl Applet for KeyboardApplet {
+    fn label(&self) -> String {
+        translate("KBD")
+    }
+
+    /// The code of the active keymap, hidden if there is no other keymap to switch to
+    fn value(&self) -> Option<String> {
+        let keymaps = self.keymaps.borrow();
+        if keymaps.list.len() < 2 {
+            None
+        } else {
+            Some(keymaps.active.clone())
+        }
+    }
+
+    /// The value is read from the keymaps shared with the bar, so there is nothing to update
+    fn update_interval(&self) -> Duration {
+        self.config.interval.unwrap_or(Duration::from_secs(60))
+    }
+
+    fn update(&mut self) {}
+
+    fn click(&mut self) -> Option<AppletAction> {
+        match &self.config.click {
+            Some(click) => Some(AppletAction::Exec(click.clone())),
+            None => Some(AppletAction::Menu(self.keymaps.borrow().list.clone())),
+        }
+    }
+
+    fn menu_chosen(&mut self, entry: &str) -> bool {
+        set_keymap(&self.keymaps, entry)
+    }
+}
+
+/// The history of notifications received by the launcher, listed in a menu when clicked
+pub struct NotificationsApplet {
+    config: AppletConfig,
+    /// The notifications, newest first
+    history: Vec<Notification>,
+}
+
+impl NotificationsApplet {
+    pub fn new(config: AppletConfig) -> Self {
+        NotificationsApplet {
+            config,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Applet for NotificationsApplet {
+    fn label(&self) -> String {
+        translate("MSG")
+    }
+
+    /// The number of notifications in the history
+    fn value(&self) -> Option<String> {
+        Some(self.history.len().to_string())
+    }
+
+    fn update_interval(&self) -> Duration {
+        self.config.interval.unwrap_or(Duration::from_secs(60))
+    }
+
+    fn update(&mut self) {}
+
+    fn click(&mut self) -> Option<AppletAction> {
+        if let Some(click) = &self.config.click {
+            return Some(AppletAction::Exec(click.clone()));
+        }
+        if self.history.is_empty() {
+            return None;
+        }
+        let mut entries: Vec<String> = self
+            .history
+            .iter()
+            .map(|notification| {
+                if notification.app_name.is_empty() {
+                    notification.summary.clone()
+                } else {
+                    format!("{}: {}", notification.app_name, notification.summary)
+                }
+            })
+            .collect();
+        entries.push(translate("Clear notifications"));
+        Some(AppletAction::Menu(entries))
+    }
+
+    fn menu_chosen(&mut self, entry: &str) -> bool {
+        if entry == translate("Clear notifications") {
+            self.history.clear();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn notify(&mut self, notification: &Notification) -> bool {
+        self.history.insert(0, notification.clone());
+        self.history.truncate(HISTORY_LEN);
+        true
+    }
+}
+
+/// Read a system file, which is often missing, so errors are only logged for debugging
+fn read_to_string(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(ok) => Some(ok),
+        Err(err) => {
+            log::debug!("failed to read {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Create a first-party applet by name
+fn new_applet(name: &str, config: AppletConfig, keymaps: &Keymaps) -> Option<Box<dyn Applet>> {
+    match name {
+        "cpu" => Some(Box::new(CpuApplet::new(config))),
+        "memory" => Some(Box::new(MemoryApplet::new(config))),
+        "battery" => Some(Box::new(BatteryApplet::new(config))),
+        "network" => Some(Box::new(NetworkApplet::new(config))),
+        "keyboard" => Some(Box::new(KeyboardApplet::new(config, keymaps.clone()))),
+        "notifications" => Some(Box::new(NotificationsApplet::new(config))),
+        _ => None,
+    }
+}
+
+/// Parse the lines of the applets config, returns the names and settings of the applets in order
+pub fn parse_applets(lines: Vec<(String, String)>) -> Vec<(String, AppletConfig)> {
+    let mut names_opt: Option<Vec<String>> = None;
+    let mut configs = BTreeMap::<String, AppletConfig>::new();
+    for (key, value) in lines {
+        if key == "applets" {
+            names_opt = Some(
+                value
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect(),
+            );
+            continue;
+        }
+
+        let Some((name, setting)) = key.rsplit_once('_') else {
+            log::error!("unknown applet setting {:?}", key);
+            continue;
+        };
+        let config = configs.entry(name.to_string()).or_default();
+        match setting {
+            "path" => config.path = Some(PathBuf::from(value)),
+            "click" => config.click = Some(value),
+            "interval" => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => config.interval = Some(Duration::from_secs(secs)),
+                _ => log::error!("invalid applet interval {:?}", value),
+            },
+            _ => log::error!("unknown applet setting {:?}", key),
+        }
+    }
+
+    let configured = names_opt.is_some();
+    let names =
+        names_opt.unwrap_or_else(|| DEFAULT_APPLETS.iter().map(|x| x.to_string()).collect());
+    let mut applets = Vec::new();
+    for name in names {
+        let config = configs.remove(&name).unwrap_or_default();
+        if SYSTEM_APPLETS.contains(&name.as_str()) && config.path.is_none() {
+            if configured {
+                log::error!("applet {:?} has no path configured", name);
+            }
+            continue;
+        }
+        applets.push((name, config));
+    }
+    applets
+}
+
+/// The applets on the bar, in order, with the time each was last updated
+///
+/// The `launcher/applets` config file can set `applets` to a comma separated list of applets,
+/// and the settings of each applet in `AppletConfig`.
+pub struct Applets {
+    applets: Vec<(Box<dyn Applet>, Option<Instant>)>,
+}
+
+impl Applets {
+    pub fn new(keymaps: &Keymaps) -> Self {
+        let mut applets = Vec::new();
+        for (name, config) in parse_applets(config_lines(CONFIG_NAME)) {
+            match new_applet(&name, config, keymaps) {
+                Some(applet) => applets.push(applet),
+                None => log::error!("unknown applet {:?}", name),
+            }
+        }
+        Self::from_applets(applets)
+    }
+
+    pub fn from_applets(applets: Vec<Box<dyn Applet>>) -> Self {
+        Applets {
+            applets: applets.into_iter().map(|applet| (applet, None)).collect(),
+        }
+    }
+
+    /// Update the applets whose update interval has passed, returns true if any value changed
+    pub fn update(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        for (applet, updated_opt) in self.applets.iter_mut() {
+            let due = match updated_opt {
+                Some(updated) => now.duration_since(*updated) >= applet.update_interval(),
+                None => true,
+            };
+            if due {
+                let value = applet.value();
+                applet.update();
+                *updated_opt = Some(now);
+                changed |= applet.value() != value;
+            }
+        }
+        changed
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Applet> {
+        self.applets.iter().map(|(applet, _)| applet.as_ref())
+    }
+
+    /// Click the applet at an index, returns what the bar should do
+    pub fn click(&mut self, i: usize) -> Option<AppletAction> {
+        self.applets.get_mut(i)?.0.click()
+    }
+
+    /// Pass an entry chosen from a menu to the applet at an index, returns true if its value
+    /// changed
+    pub fn menu_chosen(&mut self, i: usize, entry: &str) -> bool {
+        match self.applets.get_mut(i) {
+            Some((applet, _)) => applet.menu_chosen(entry),
+            None => false,
+        }
+    }
+
+    /// Pass a notification to every applet, returns true if any value changed
+    pub fn notify(&mut self, notification: &Notification) -> bool {
+        let mut changed = false;
+        for (applet, _) in self.applets.iter_mut() {
+            changed |= applet.notify(notification);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::lines;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/applets")
+            .join(name)
+    }
+
+    fn names(applets: Vec<(String, AppletConfig)>) -> Vec<String> {
+        applets.into_iter().map(|(name, _)| name).collect()
+    }
+
+    fn config(name: &str) -> AppletConfig {
+        AppletConfig {
+            path: Some(fixture(name)),
+            ..AppletConfig::default()
+        }
+    }
+
+    #[test]
+    fn cpu() {
+        let stat = fs::read_to_string(fixture("stat")).unwrap();
+        assert_eq!(parse_cpu_times(&stat), Some((200, 1000)));
+        assert_eq!(cpu_load((200, 1000), (275, 1100)), Some(75));
+        assert_eq!(cpu_load((200, 1000), (200, 1000)), None);
+        assert_eq!(parse_cpu_times("intr 1 2 3"), None);
+
+        let mut applet = CpuApplet::new(config("stat"));
+        applet.update();
+        assert_eq!(applet.value().as_deref(), Some("20%"));
+        // Without a change in total time the last load is kept
+        applet.update();
+        assert_eq!(applet.value().as_deref(), Some("20%"));
+    }
+
+    #[test]
+    fn memory() {
+        let meminfo = fs::read_to_string(fixture("meminfo")).unwrap();
+        assert_eq!(parse_memory_use(&meminfo), Some(75));
+        assert_eq!(
+            parse_memory_use("MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 50 kB\n"),
+            Some(80)
+        );
+        assert_eq!(parse_memory_use("MemFree: 100 kB\n"), None);
+
+        let mut applet = MemoryApplet::new(config("meminfo"));
+        applet.update();
+        assert_eq!(applet.value().as_deref(), Some("75%"));
+    }
+
+    #[test]
+    fn battery() {
+        let mut applet = BatteryApplet::new(config("BAT0"));
+        applet.update();
+        assert_eq!(applet.value().as_deref(), Some("85%+"));
+
+        let mut missing = BatteryApplet::new(config("BAT1"));
+        missing.update();
+        assert_eq!(missing.value(), None);
+    }
+
+    #[test]
+    fn network() {
+        assert_eq!(network_up(&fixture("net")), Some(true));
+        assert_eq!(network_up(&fixture("net_down")), Some(false));
+        assert_eq!(network_up(&fixture("missing")), None);
+
+        let mut applet = NetworkApplet::new(config("net_down"));
+        applet.update();
+        assert_eq!(applet.value(), Some(translate("down")));
+    }
+
+    #[test]
+    fn notifications() {
+        let mut applet = NotificationsApplet::new(AppletConfig::default());
+        assert_eq!(applet.value().as_deref(), Some("0"));
+        assert_eq!(applet.click(), None);
+
+        for summary in ["First", "Second"] {
+            assert!(applet.notify(&Notification {
+                app_name: "test".to_string(),
+                summary: summary.to_string(),
+                ..Notification::default()
+            }));
+        }
+        assert_eq!(applet.value().as_deref(), Some("2"));
+        let Some(AppletAction::Menu(entries)) = applet.click() else {
+            panic!("no history menu");
+        };
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], "test: Second");
+
+        assert!(!applet.menu_chosen(&entries[0]));
+        assert!(applet.menu_chosen(&entries[2]));
+        assert_eq!(applet.value().as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn configs() {
+        // System applets are only shown by default once their path is configured
+        assert_eq!(
+            names(parse_applets(Vec::new())),
+            ["keyboard", "notifications"]
+        );
+        let applets = parse_applets(lines(&[
+            ("memory_path", "/scheme/memory"),
+            ("memory_interval", "5"),
+            ("notifications_click", "notifications"),
+        ]));
+        assert_eq!(
+            names(applets.clone()),
+            ["memory", "keyboard", "notifications"]
+        );
+        assert_eq!(applets[0].1.path, Some(PathBuf::from("/scheme/memory")));
+        assert_eq!(applets[0].1.interval, Some(Duration::from_secs(5)));
+        assert_eq!(applets[2].1.click.as_deref(), Some("notifications"));
+
+        let applets = parse_applets(lines(&[
+            ("applets", "notifications, cpu, battery"),
+            ("battery_path", "/sys/class/power_supply/BAT1"),
+        ]));
+        assert_eq!(names(applets), ["notifications", "battery"]);
+    }
+
+    #[test]
+    fn intervals() {
+        let mut applets = Applets::from_applets(vec![Box::new(MemoryApplet::new(AppletConfig {
+            interval: Some(Duration::from_secs(5)),
+            ..config("meminfo")
+        }))]);
+        let start = Instant::now();
+        assert!(applets.update(start));
+        assert!(!applets.update(start + Duration::from_secs(6)));
+        assert_eq!(
+            applets.iter().next().unwrap().value().as_deref(),
+            Some("75%")
+        );
+        assert_eq!(applets.click(0), None);
+        assert_eq!(applets.click(1), None);
+    }
+}
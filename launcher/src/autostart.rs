@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+use freedesktop_entry_parser::Entry;
+
+use crate::package::{shown_in, Package};
+
+/// The key holding the number of seconds to wait before launching an autostart entry
+const DELAY_KEY: &str = "X-Redox-Autostart-Delay";
+
+/// A package launched when the session starts, from an `autostart` desktop entry
+#[derive(Clone)]
+pub struct AutostartEntry {
+    pub package: Package,
+    /// How long after the bar starts the package is launched
+    pub delay: Duration,
+}
+
+impl AutostartEntry {
+    /// Parse an autostart desktop entry, returns None if it is hidden, not installed or not
+    /// shown in the current desktop
+    pub fn from_entry(id: String, entry: &Entry) -> Option<Self> {
+        let package = Package::from_entry(id, entry)?;
+        if package.exec.is_empty() {
+            log::warn!("{}: autostart entry has no Exec", package.id);
+            return None;
+        }
+        // NoDisplay only hides entries from menus, so only the desktop lists are checked
+        let section = entry.section("Desktop Entry");
+        if !shown_in(section.attr("OnlyShowIn"), section.attr("NotShowIn")) {
+            log::debug!("{}: not autostarted in this desktop", package.id);
+            return None;
+        }
+
+        let delay = match section.attr(DELAY_KEY).map(|x| x.parse::<u64>()) {
+            Some(Ok(secs)) => Duration::from_secs(secs),
+            Some(Err(err)) => {
+                log::warn!("{}: invalid {}: {}", package.id, DELAY_KEY, err);
+                Duration::ZERO
+            }
+            None => Duration::ZERO,
+        };
+        Some(AutostartEntry { package, delay })
+    }
+}
+
+/// Find the entries in the `autostart` directories of the XDG config directories
+///
+/// An entry in `XDG_CONFIG_HOME` replaces a system-wide entry with the same file name, so a user
+/// can disable one with `Hidden=true`.
+pub fn autostart_entries() -> Vec<AutostartEntry> {
+    let xdg_dirs = match xdg::BaseDirectories::new() {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to find config directories: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut entries = Vec::new();
+    for path in xdg_dirs.list_config_files_once("autostart") {
+        if path.extension().and_then(|x| x.to_str()) != Some("desktop") {
+            continue;
+        }
+        let Some(id) = path.file_name().and_then(|x| x.to_str()) else {
+            continue;
+        };
+        let entry = match freedesktop_entry_parser::parse_entry(&path) {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::error!("failed to parse {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        if let Some(entry) = AutostartEntry::from_entry(id.to_string(), &entry) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by(|a, b| a.package.id.cmp(&b.package.id));
+    entries
+}
+
+/// The autostart entries waiting to be launched
+pub struct Autostart {
+    started: Instant,
+    pending: Vec<AutostartEntry>,
+}
+
+impl Autostart {
+    pub fn new(started: Instant) -> Self {
+        Self::from_entries(autostart_entries(), started)
+    }
+
+    fn from_entries(pending: Vec<AutostartEntry>, started: Instant) -> Self {
+        Autostart { started, pending }
+    }
+
+    /// Take the packages whose delay has passed
+    pub fn due(&mut self, now: Instant) -> Vec<Package> {
+        let elapsed = now.duration_since(self.started);
+        let mut due = Vec::new();
+        self.pending.retain(|entry| {
+            if entry.delay <= elapsed {
+                due.push(entry.package.clone());
+                false
+            } else {
+                true
+            }
+        });
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::current_desktops;
+
+    fn autostart_entry(entry: &str) -> Option<AutostartEntry> {
+        let entry = Entry::parse(entry).unwrap();
+        AutostartEntry::from_entry("test.desktop".to_string(), &entry)
+    }
+
+    #[test]
+    fn entries() {
+        let entry = autostart_entry("[Desktop Entry]\nName=Test\nExec=test\nNoDisplay=true\n");
+        assert_eq!(entry.unwrap().delay, Duration::ZERO);
+        let delayed = autostart_entry(&format!(
+            "[Desktop Entry]\nName=Test\nExec=test\n{}=5\n",
+            DELAY_KEY
+        ));
+        assert_eq!(delayed.unwrap().delay, Duration::from_secs(5));
+        assert!(autostart_entry("[Desktop Entry]\nName=Test\nExec=test\nHidden=true\n").is_none());
+        assert!(autostart_entry("[Desktop Entry]\nName=Test\n").is_none());
+    }
+
+    #[test]
+    fn show_in() {
+        let desktop = &current_desktops()[0];
+        let only = format!("[Desktop Entry]\nName=Test\nExec=test\nOnlyShowIn={desktop};\n");
+        assert!(autostart_entry(&only).is_some());
+        let only_other = "[Desktop Entry]\nName=Test\nExec=test\nOnlyShowIn=NotADesktop;\n";
+        assert!(autostart_entry(only_other).is_none());
+        let not = format!("[Desktop Entry]\nName=Test\nExec=test\nNotShowIn={desktop};\n");
+        assert!(autostart_entry(&not).is_none());
+    }
+
+    #[test]
+    fn delays() {
+        let entry = |id: &str, secs: u64| {
+            let mut package = Package::new();
+            package.id = id.to_string();
+            package.exec = id.to_string();
+            AutostartEntry {
+                package,
+                delay: Duration::from_secs(secs),
+            }
+        };
+        let started = Instant::now();
+        let mut autostart = Autostart::from_entries(
+            vec![entry("now", 0), entry("later", 10), entry("soon", 2)],
+            started,
+        );
+        let ids = |packages: Vec<Package>| -> Vec<String> {
+            packages.into_iter().map(|package| package.id).collect()
+        };
+        assert_eq!(ids(autostart.due(started)), vec!["now"]);
+        assert!(autostart.due(started + Duration::from_secs(1)).is_empty());
+        assert_eq!(
+            ids(autostart.due(started + Duration::from_secs(15))),
+            vec!["later", "soon"]
+        );
+        assert!(autostart.due(started + Duration::from_secs(20)).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::{Datelike, Month, NaiveDate};
+use orbclient::Renderer;
+use orbfont::Font;
+
+use crate::theme::{BAR_COLOR, BAR_HIGHLIGHT_COLOR, TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+/// The number of week rows, fixed so that the popup does not change size between months
+const WEEKS: u32 = 6;
+
+/// A month calendar, with weeks starting on Monday
+pub struct Calendar {
+    pub year: i32,
+    pub month: u32,
+    pub today: NaiveDate,
+}
+
+impl Calendar {
+    pub fn new(today: NaiveDate) -> Self {
+        Calendar {
+            year: today.year(),
+            month: today.month(),
+            today,
+        }
+    }
+
+    /// The width of a day cell, in pixels
+    pub fn cell_size(font_size: i32) -> u32 {
+        font_size as u32 * 2
+    }
+
+    /// The size of the calendar, in pixels: a header, the weekday names, and the weeks
+    pub fn size(font_size: i32) -> (u32, u32) {
+        let cell = Self::cell_size(font_size);
+        (7 * cell, (2 + WEEKS) * cell)
+    }
+
+    pub fn prev_month(&mut self) {
+        if self.month == 1 {
+            self.year -= 1;
+            self.month = 12;
+        } else {
+            self.month -= 1;
+        }
+    }
+
+    pub fn next_month(&mut self) {
+        if self.month == 12 {
+            self.year += 1;
+            self.month = 1;
+        } else {
+            self.month += 1;
+        }
+    }
+
+    /// The title of the month, like `March 2024`
+    pub fn title(&self) -> String {
+        match Month::try_from(self.month as u8) {
+            Ok(month) => format!("{} {}", month.name(), self.year),
+            Err(_) => format!("{}-{:>02}", self.year, self.month),
+        }
+    }
+
+    /// The days of the month, laid out in weeks starting on Monday
+    pub fn weeks(&self) -> Vec<[Option<u32>; 7]> {
+        let mut weeks = Vec::new();
+        let Some(first) = NaiveDate::from_ymd_opt(self.year, self.month, 1) else {
+            return weeks;
+        };
+
+        let mut week = [None; 7];
+        let mut weekday = first.weekday().num_days_from_monday() as usize;
+        let mut date = first;
+        while date.month() == self.month {
+            week[weekday] = Some(date.day());
+            weekday += 1;
+            if weekday == 7 {
+                weeks.push(week);
+                week = [None; 7];
+                weekday = 0;
+            }
+            match date.succ_opt() {
+                Some(next) => date = next,
+                None => break,
+            }
+        }
+        if weekday > 0 {
+            weeks.push(week);
+        }
+        weeks
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R, font: &Font, font_size: i32) {
+        let cell = Self::cell_size(font_size) as i32;
+
+        renderer.set(BAR_COLOR);
+
+        // Header with month navigation
+        let draw_centered = |renderer: &mut R, text: &str, x: i32, y: i32, w: i32, color| {
+            let text = font.render(text, font_size as f32);
+            text.draw(
+                renderer,
+                x + (w - text.width() as i32) / 2,
+                y + (cell - text.height() as i32) / 2,
+                color,
+            );
+        };
+        draw_centered(renderer, "<", 0, 0, cell, TEXT_HIGHLIGHT_COLOR);
+        draw_centered(
+            renderer,
+            &self.title(),
+            cell,
+            0,
+            5 * cell,
+            TEXT_HIGHLIGHT_COLOR,
+        );
+        draw_centered(renderer, ">", 6 * cell, 0, cell, TEXT_HIGHLIGHT_COLOR);
+
+        for (i, weekday) in WEEKDAYS.iter().enumerate() {
+            draw_centered(renderer, weekday, i as i32 * cell, cell, cell, TEXT_COLOR);
+        }
+
+        let mut y = 2 * cell;
+        for week in self.weeks() {
+            for (i, day_opt) in week.iter().enumerate() {
+                let Some(day) = day_opt else {
+                    continue;
+                };
+                let x = i as i32 * cell;
+                let is_today = self.today.year() == self.year
+                    && self.today.month() == self.month
+                    && self.today.day() == *day;
+                if is_today {
+                    renderer.rect(x, y, cell as u32, cell as u32, BAR_HIGHLIGHT_COLOR);
+                }
+                draw_centered(
+                    renderer,
+                    &day.to_string(),
+                    x,
+                    y,
+                    cell,
+                    if is_today {
+                        TEXT_HIGHLIGHT_COLOR
+                    } else {
+                        TEXT_COLOR
+                    },
+                );
+            }
+            y += cell;
+        }
+
+        renderer.sync();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(year: i32, month: u32) -> Calendar {
+        Calendar::new(NaiveDate::from_ymd_opt(year, month, 1).unwrap())
+    }
+
+    #[test]
+    fn weeks() {
+        // September 2024 starts on a Sunday and needs all six rows
+        let weeks = calendar(2024, 9).weeks();
+        assert_eq!(weeks.len(), 6);
+        assert_eq!(weeks[0], [None, None, None, None, None, None, Some(1)]);
+        assert_eq!(weeks[1][0], Some(2));
+        assert_eq!(weeks[5], [Some(30), None, None, None, None, None, None]);
+
+        // February of a leap year has 29 days
+        let weeks = calendar(2024, 2).weeks();
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][3], Some(1));
+        assert_eq!(
+            weeks[4],
+            [Some(26), Some(27), Some(28), Some(29), None, None, None]
+        );
+
+        // February 2021 starts on a Monday and fills exactly four weeks
+        let weeks = calendar(2021, 2).weeks();
+        assert_eq!(weeks.len(), 4);
+        assert_eq!(weeks[3][6], Some(28));
+
+        let mut invalid = calendar(2024, 1);
+        invalid.month = 13;
+        assert!(invalid.weeks().is_empty());
+    }
+
+    #[test]
+    fn months() {
+        let mut calendar = calendar(2024, 12);
+        assert_eq!(calendar.title(), "December 2024");
+        calendar.next_month();
+        assert_eq!((calendar.year, calendar.month), (2025, 1));
+        assert_eq!(calendar.title(), "January 2025");
+        calendar.prev_month();
+        calendar.prev_month();
+        assert_eq!((calendar.year, calendar.month), (2024, 11));
+
+        calendar.month = 13;
+        assert_eq!(calendar.title(), "2024-13");
+    }
+}
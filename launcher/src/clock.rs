@@ -0,0 +1,156 @@
+use std::fmt::Display;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, TimeZone};
+
+use crate::config::config_lines;
+
+/// The config file holding clock settings
+const CONFIG_NAME: &str = "clock";
+
+const FORMAT_24H: &str = "%H:%M";
+const FORMAT_12H: &str = "%I:%M %p";
+const DATE_FORMAT: &str = "%a %b %e";
+
+/// Check that a strftime-style format has no invalid specifiers
+fn valid_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| item != Item::Error)
+}
+
+/// The bar clock, formatted in the local timezone
+///
+/// The local timezone comes from `TZ`, or the zoneinfo database through `/etc/localtime`. The
+/// `launcher/clock` config file can set `format` to `24h`, `12h`, or a strftime-style format,
+/// and `date` to `true`, `false`, or a strftime-style format for a second line with the date.
+pub struct Clock {
+    format: String,
+    date_format: Option<String>,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        let mut clock = Clock {
+            format: FORMAT_24H.to_string(),
+            date_format: None,
+        };
+        for (key, value) in config_lines(CONFIG_NAME) {
+            match key.as_str() {
+                "format" => match value.as_str() {
+                    "24h" => clock.format = FORMAT_24H.to_string(),
+                    "12h" => clock.format = FORMAT_12H.to_string(),
+                    _ if valid_format(&value) => clock.format = value,
+                    _ => log::error!("invalid clock format {:?}", value),
+                },
+                "date" => match value.as_str() {
+                    "true" => clock.date_format = Some(DATE_FORMAT.to_string()),
+                    "false" | "" => clock.date_format = None,
+                    _ if valid_format(&value) => clock.date_format = Some(value),
+                    _ => log::error!("invalid date format {:?}", value),
+                },
+                _ => log::error!("unknown clock setting {:?}", key),
+            }
+        }
+        clock
+    }
+
+    /// Format a timestamp in seconds as time and optional date text, in the local timezone
+    pub fn format(&self, timestamp: i64) -> (String, Option<String>) {
+        self.format_in(timestamp, &Local)
+    }
+
+    /// Format a timestamp in seconds as time and optional date text, in a given timezone
+    pub fn format_in<Tz: TimeZone>(&self, timestamp: i64, tz: &Tz) -> (String, Option<String>)
+    where
+        Tz::Offset: Display,
+    {
+        let Some(datetime) = tz.timestamp_opt(timestamp, 0).earliest() else {
+            return (String::new(), None);
+        };
+        (
+            format_datetime(&datetime, &self.format),
+            self.date_format
+                .as_ref()
+                .map(|date_format| format_datetime(&datetime, date_format)),
+        )
+    }
+}
+
+fn format_datetime<Tz: TimeZone>(datetime: &DateTime<Tz>, format: &str) -> String
+where
+    Tz::Offset: Display,
+{
+    datetime.format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    // 2024-03-09 23:30:15 UTC, a Saturday
+    const TIMESTAMP: i64 = 1710027015;
+
+    fn clock(format: &str, date_format: Option<&str>) -> Clock {
+        Clock {
+            format: format.to_string(),
+            date_format: date_format.map(|x| x.to_string()),
+        }
+    }
+
+    #[test]
+    fn utc_24h() {
+        let clock = clock(FORMAT_24H, None);
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(
+            clock.format_in(TIMESTAMP, &utc),
+            ("23:30".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn offset_crosses_midnight() {
+        let clock = clock(FORMAT_24H, Some(DATE_FORMAT));
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        assert_eq!(
+            clock.format_in(TIMESTAMP, &cet),
+            ("00:30".to_string(), Some("Sun Mar 10".to_string()))
+        );
+        let pst = FixedOffset::west_opt(8 * 3600).unwrap();
+        assert_eq!(
+            clock.format_in(TIMESTAMP, &pst),
+            ("15:30".to_string(), Some("Sat Mar  9".to_string()))
+        );
+    }
+
+    #[test]
+    fn half_hour_offset() {
+        let clock = clock(FORMAT_24H, None);
+        let ist = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        assert_eq!(clock.format_in(TIMESTAMP, &ist).0, "05:00");
+    }
+
+    #[test]
+    fn format_12h() {
+        let clock = clock(FORMAT_12H, None);
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(clock.format_in(TIMESTAMP, &utc).0, "11:30 PM");
+        assert_eq!(clock.format_in(TIMESTAMP + 3600, &utc).0, "12:30 AM");
+    }
+
+    #[test]
+    fn custom_format() {
+        let clock = clock("%H:%M:%S", Some("%Y-%m-%d"));
+        let utc = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(
+            clock.format_in(TIMESTAMP, &utc),
+            ("23:30:15".to_string(), Some("2024-03-09".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_format() {
+        assert!(valid_format("%H:%M"));
+        assert!(!valid_format("%Q"));
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Find every `launcher/<name>` config file, system-wide first and per-user last
+pub fn config_paths(name: &str) -> Vec<PathBuf> {
+    match xdg::BaseDirectories::with_prefix("launcher") {
+        Ok(xdg_dirs) => xdg_dirs.find_config_files(name).collect(),
+        Err(err) => {
+            log::warn!("failed to find config directories: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Read the `key=value` lines of every `launcher/<name>` config file, in the order that they
+/// should be applied, so later entries override earlier ones
+pub fn config_lines(name: &str) -> Vec<(String, String)> {
+    let mut lines = Vec::new();
+    for path in config_paths(name) {
+        let data = match fs::read_to_string(&path) {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::warn!("failed to read {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) => {
+                    lines.push((key.trim().to_string(), value.trim().to_string()));
+                }
+                None => {
+                    log::error!("invalid line in {}: {}", path.display(), line);
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Find the path of the `launcher/<name>` file in the per-user data directory, creating the
+/// directory if needed
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    let xdg_dirs = match xdg::BaseDirectories::with_prefix("launcher") {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to find data directories: {}", err);
+            return None;
+        }
+    };
+    match xdg_dirs.place_data_file(name) {
+        Ok(ok) => Some(ok),
+        Err(err) => {
+            log::warn!("failed to create data directory for {}: {}", name, err);
+            None
+        }
+    }
+}
+
+/// The modification times of every `launcher/<name>` config file, used to detect changes
+pub fn config_mtimes(name: &str) -> Vec<(PathBuf, Option<SystemTime>)> {
+    config_paths(name)
+        .into_iter()
+        .map(|path| {
+            let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, mtime)
+        })
+        .collect()
+}
+
+/// Set a `key=value` line in the per-user `launcher/<name>` config file, keeping other lines
+pub fn set_config_value(name: &str, key: &str, value: &str) -> io::Result<()> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("launcher")?;
+    let path = xdg_dirs.place_config_file(name)?;
+
+    let mut data = String::new();
+    let mut found = false;
+    if let Ok(old_data) = fs::read_to_string(&path) {
+        for line in old_data.lines() {
+            match line.split_once('=') {
+                Some((line_key, _)) if line_key.trim() == key => {
+                    if !found {
+                        data.push_str(&format!("{}={}\n", key, value));
+                        found = true;
+                    }
+                }
+                _ => {
+                    data.push_str(line);
+                    data.push('\n');
+                }
+            }
+        }
+    }
+    if !found {
+        data.push_str(&format!("{}={}\n", key, value));
+    }
+
+    fs::write(&path, data)
+}
+
+/// Helpers for the tests of modules that parse config lines
+#[cfg(test)]
+pub mod test_support {
+    /// Build `key=value` lines like those returned by `config_lines`
+    pub fn lines(x: &[(&str, &str)]) -> Vec<(String, String)> {
+        x.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+}
@@ -0,0 +1,25 @@
+use orbutils_shared::display::get_display_rects;
+
+use crate::placement::Rect;
+
+/// Get the rectangles of every screen, or of the whole display if they cannot be enumerated
+pub fn display_rects() -> Vec<Rect> {
+    match get_display_rects() {
+        Ok(rects) if !rects.is_empty() => rects
+            .into_iter()
+            .map(|rect| (rect.x, rect.y, rect.width, rect.height))
+            .collect(),
+        result => {
+            if let Err(err) = result {
+                log::warn!("failed to get display rects: {}", err);
+            }
+            match orbclient::get_display_size() {
+                Ok((width, height)) => vec![(0, 0, width, height)],
+                Err(err) => {
+                    log::error!("failed to get display size: {}", err);
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
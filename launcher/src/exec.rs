@@ -0,0 +1,313 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::config_lines;
+use crate::package::Package;
+
+/// The config file holding application launch settings
+const CONFIG_NAME: &str = "applications";
+
+/// The terminal used for `Terminal=true` desktop entries, the command is appended to it
+const DEFAULT_TERMINAL: &str = "cosmic-term -e";
+
+/// The file manager used to show files, the path is appended to it
+const DEFAULT_FILE_MANAGER: &str = "cosmic-files";
+
+/// Get a setting from `launcher/applications`
+fn application_setting(name: &str, default: &str) -> String {
+    let mut setting = default.to_string();
+    for (key, value) in config_lines(CONFIG_NAME) {
+        if key == name {
+            setting = value;
+        }
+    }
+    setting
+}
+
+/// Get the terminal command line from the `terminal` key of `launcher/applications`
+fn terminal_args() -> Vec<String> {
+    let terminal = application_setting("terminal", DEFAULT_TERMINAL);
+    match shlex::split(&terminal) {
+        Some(args) if !args.is_empty() => args,
+        _ => {
+            log::error!("invalid terminal {:?}", terminal);
+            shlex::split(DEFAULT_TERMINAL).unwrap_or_default()
+        }
+    }
+}
+
+/// Get an exec string opening a path in the file manager from the `file_manager` key of
+/// `launcher/applications`
+pub fn file_manager_exec(path: &str) -> Option<String> {
+    let file_manager = application_setting("file_manager", DEFAULT_FILE_MANAGER);
+    let path = shlex::try_quote(path).ok()?;
+    Some(format!("{} {}", file_manager, path))
+}
+
+/// Check if an Exec string takes a list of files or URLs with `%F` or `%U`
+pub fn exec_takes_list(exec: &str) -> bool {
+    shlex::split(exec)
+        .unwrap_or_default()
+        .iter()
+        .any(|arg| arg == "%F" || arg == "%U")
+}
+
+/// Expand the field codes in a single argument, returns None if an invalid code is used
+fn expand_arg(arg: &str, package_opt: Option<&Package>, paths: &[&str]) -> Option<String> {
+    let mut expanded = String::new();
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'f' | 'u' => {
+                if let Some(path) = paths.first() {
+                    expanded.push_str(path);
+                }
+            }
+            'c' => {
+                if let Some(package) = package_opt {
+                    expanded.push_str(&package.name);
+                }
+            }
+            'k' => {
+                if let Some(path) = package_opt.and_then(|x| x.desktop_path.as_ref()) {
+                    expanded.push_str(&path.display().to_string());
+                }
+            }
+            '%' => expanded.push('%'),
+            // Deprecated field codes are removed
+            'd' | 'D' | 'n' | 'N' | 'v' | 'm' => {}
+            _ => return None,
+        }
+    }
+    Some(expanded)
+}
+
+/// Expand the field codes of an Exec string into arguments, returns None if it is invalid
+///
+/// See https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html
+pub fn exec_args(exec: &str, package_opt: Option<&Package>, paths: &[&str]) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    for arg in shlex::split(exec)? {
+        match arg.as_str() {
+            "%F" | "%U" => {
+                args.extend(paths.iter().map(|path| path.to_string()));
+            }
+            "%i" => {
+                if let Some(icon) = package_opt.and_then(|x| x.icon_name.as_ref()) {
+                    args.push("--icon".to_string());
+                    args.push(icon.clone());
+                }
+            }
+            _ => {
+                let expanded = expand_arg(&arg, package_opt, paths)?;
+                // Arguments that are only field codes are dropped if they expand to nothing
+                if !expanded.is_empty() || !arg.starts_with('%') {
+                    args.push(expanded);
+                }
+            }
+        }
+    }
+    if args.is_empty() {
+        None
+    } else {
+        Some(args)
+    }
+}
+
+/// Create a command from an Exec string, with the settings of the package it came from
+pub fn exec_to_command(
+    exec: &str,
+    package_opt: Option<&Package>,
+    paths: &[&str],
+) -> Option<Command> {
+    let mut args = exec_args(exec, package_opt, paths)?;
+    if package_opt.is_some_and(|x| x.terminal) {
+        let mut terminal = terminal_args();
+        terminal.append(&mut args);
+        args = terminal;
+    }
+
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    if let Some(working_dir) = package_opt.and_then(|x| x.working_dir.as_ref()) {
+        command.current_dir(working_dir);
+    }
+    Some(command)
+}
+
+/// Check if an executable exists, searching PATH if it is not a path, for `TryExec`
+pub fn executable_exists(name: &str) -> bool {
+    if name.contains('/') {
+        return Path::new(name).is_file();
+    }
+    match env::var_os("PATH") {
+        Some(paths) => env::split_paths(&paths).any(|dir| dir.join(name).is_file()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn package() -> Package {
+        let mut package = Package::new();
+        package.name = "Text Editor".to_string();
+        package.icon_name = Some("text-editor".to_string());
+        package.desktop_path = Some(PathBuf::from("/usr/share/applications/editor.desktop"));
+        package
+    }
+
+    fn args(exec: &str, paths: &[&str]) -> Option<Vec<String>> {
+        exec_args(exec, Some(&package()), paths)
+    }
+
+    #[test]
+    fn single_file() {
+        assert_eq!(
+            args("editor %f", &["/home/user/a.txt"]),
+            Some(vec!["editor".into(), "/home/user/a.txt".into()])
+        );
+        assert_eq!(
+            args("editor %u", &["a.txt", "b.txt"]),
+            Some(vec!["editor".into(), "a.txt".into()])
+        );
+    }
+
+    #[test]
+    fn file_list() {
+        assert_eq!(
+            args("editor --new %F", &["a.txt", "b c.txt"]),
+            Some(vec![
+                "editor".into(),
+                "--new".into(),
+                "a.txt".into(),
+                "b c.txt".into()
+            ])
+        );
+        assert_eq!(
+            args(
+                "browser %U",
+                &["https://redox-os.org", "file:///tmp/a.html"]
+            ),
+            Some(vec![
+                "browser".into(),
+                "https://redox-os.org".into(),
+                "file:///tmp/a.html".into()
+            ])
+        );
+    }
+
+    #[test]
+    fn no_files() {
+        assert_eq!(args("editor %f", &[]), Some(vec!["editor".into()]));
+        assert_eq!(args("editor %F", &[]), Some(vec!["editor".into()]));
+        assert_eq!(
+            args("editor %U --flag", &[]),
+            Some(vec!["editor".into(), "--flag".into()])
+        );
+    }
+
+    #[test]
+    fn embedded_code() {
+        assert_eq!(
+            args("editor --open=%f", &["a.txt"]),
+            Some(vec!["editor".into(), "--open=a.txt".into()])
+        );
+        assert_eq!(
+            args("editor --open=%f", &[]),
+            Some(vec!["editor".into(), "--open=".into()])
+        );
+    }
+
+    #[test]
+    fn icon_name_and_location() {
+        assert_eq!(
+            args("editor %i --title %c %k", &[]),
+            Some(vec![
+                "editor".into(),
+                "--icon".into(),
+                "text-editor".into(),
+                "--title".into(),
+                "Text Editor".into(),
+                "/usr/share/applications/editor.desktop".into()
+            ])
+        );
+        assert_eq!(
+            exec_args("editor %i %c %k", None, &[]),
+            Some(vec!["editor".into()])
+        );
+    }
+
+    #[test]
+    fn percent() {
+        assert_eq!(
+            args("printf 100%%", &[]),
+            Some(vec!["printf".into(), "100%".into()])
+        );
+        assert_eq!(
+            args("printf %%", &[]),
+            Some(vec!["printf".into(), "%".into()])
+        );
+    }
+
+    #[test]
+    fn deprecated_codes() {
+        assert_eq!(
+            args("editor %d %D %n %N %v %m %f", &["a.txt"]),
+            Some(vec!["editor".into(), "a.txt".into()])
+        );
+    }
+
+    #[test]
+    fn invalid_codes() {
+        assert_eq!(args("editor %x", &[]), None);
+        assert_eq!(args("editor --files=%F", &["a.txt"]), None);
+        assert_eq!(args("editor 100%", &[]), None);
+        assert_eq!(args("", &[]), None);
+        assert_eq!(args("editor \"unterminated", &[]), None);
+    }
+
+    #[test]
+    fn takes_list() {
+        assert!(exec_takes_list("editor %F"));
+        assert!(exec_takes_list("browser %U"));
+        assert!(!exec_takes_list("editor %f"));
+        assert!(!exec_takes_list("editor"));
+    }
+
+    #[test]
+    fn working_dir() {
+        let mut package = package();
+        package.working_dir = Some(PathBuf::from("/tmp"));
+        let command = exec_to_command("editor %f", Some(&package), &["a.txt"]).unwrap();
+        assert_eq!(command.get_program(), "editor");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["a.txt"]);
+        assert_eq!(command.get_current_dir(), Some(Path::new("/tmp")));
+    }
+
+    #[test]
+    fn terminal() {
+        let mut package = package();
+        package.terminal = true;
+        let command = exec_to_command("htop", Some(&package), &[]).unwrap();
+        let mut args: Vec<String> = vec![command.get_program().to_string_lossy().into()];
+        args.extend(command.get_args().map(|x| x.to_string_lossy().into_owned()));
+        assert_eq!(args.last().map(|x| x.as_str()), Some("htop"));
+        assert!(args.len() > 1);
+    }
+
+    #[test]
+    fn try_exec() {
+        assert!(executable_exists("/bin/sh"));
+        assert!(!executable_exists("/nonexistent/program"));
+        assert!(!executable_exists("nonexistent-program-for-launcher-tests"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use orbclient::image::Image;
+use orbclient::{Color, Renderer};
+
+use crate::icon_theme::FALLBACK_ICON;
+use crate::package::IconSource;
+use crate::{load_icon, load_icon_small, load_icon_svg};
+
+/// Identifies rasterized icon cache files and their format version
+const DISK_MAGIC: &[u8; 8] = b"LICON001";
+
+/// The length of the header of a rasterized icon cache file
+const DISK_HEADER_LEN: usize = 8 + 8 + 4 + 4 + 4;
+
+/// The icon file, its modification time, whether the icon is small, and the scale the icon was
+/// loaded for
+type IconKey = (PathBuf, SystemTime, bool, isize);
+
+thread_local! {
+    static ICONS: RefCell<BTreeMap<IconKey, Rc<Image>>> = const { RefCell::new(BTreeMap::new()) };
+    /// The directory of the rasterized icon cache, None until `use_disk_cache` is called
+    static DISK_CACHE_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Keep rasterized icons in the user's cache directory, so they load faster the next time
+pub fn use_disk_cache() {
+    let dir_opt = disk_cache_dir();
+    DISK_CACHE_DIR.with(|dir| *dir.borrow_mut() = dir_opt);
+}
+
+/// Get an icon, loading it only if its file is not already loaded at the current scale
+///
+/// Icons that fail to load are replaced by the generic application icon, or by an empty image if
+/// that fails too or there is no icon. Failures are not cached, so an icon installed after its
+/// package is loaded when the packages are reloaded.
+pub fn icon(source: &IconSource, small: bool) -> Rc<Image> {
+    let cache_dir_opt = DISK_CACHE_DIR.with(|dir| dir.borrow().clone());
+    let fallback = IconSource::Name(FALLBACK_ICON.to_string());
+    match load(source, small, cache_dir_opt.as_deref()) {
+        Some(image) => image,
+        None if !matches!(source, IconSource::None) && *source != fallback => {
+            log::warn!("failed to load icon {:?}, using {}", source, FALLBACK_ICON);
+            icon(&fallback, small)
+        }
+        None => {
+            log::warn!("failed to load icon {:?}", source);
+            Rc::new(Image::new(0, 0))
+        }
+    }
+}
+
+/// Find and load an icon, sharing it with other icons loaded from the same file, and using the
+/// rasterized icon cache in a directory when it is up to date
+fn load(source: &IconSource, small: bool, cache_dir_opt: Option<&Path>) -> Option<Rc<Image>> {
+    let path = source.clone().lookup(small)?.to_path_buf();
+    let size = if small {
+        crate::icon_small_size()
+    } else {
+        crate::icon_size()
+    } as u32;
+    let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+    let key = (
+        path.clone(),
+        mtime,
+        small,
+        crate::SCALE.load(Ordering::Relaxed),
+    );
+    if let Some(image) = ICONS.with(|icons| icons.borrow().get(&key).cloned()) {
+        return Some(image);
+    }
+
+    log::debug!("loading {}", path.display());
+    let image = Rc::new(load_file(&path, size, mtime, small, cache_dir_opt)?);
+    ICONS.with(|icons| {
+        let mut icons = icons.borrow_mut();
+        // Icons loaded before the file changed are no longer used
+        icons.retain(|(icon_path, icon_mtime, _, _), _| *icon_path != path || *icon_mtime == mtime);
+        icons.insert(key, image.clone());
+    });
+    Some(image)
+}
+
+/// Load an icon file, using the rasterized icon cache in a directory when it is up to date
+fn load_file(
+    path: &Path,
+    size: u32,
+    mtime: SystemTime,
+    small: bool,
+    cache_dir_opt: Option<&Path>,
+) -> Option<Image> {
+    let cache_path_opt = cache_dir_opt.map(|dir| disk_cache_path(dir, path, size));
+    if let Some(cache_path) = &cache_path_opt {
+        if let Ok(data) = fs::read(cache_path) {
+            if let Some(image) = decode(&data, mtime) {
+                return Some(image);
+            }
+        }
+    }
+
+    let image = if path.extension() == Some(OsStr::new("png")) {
+        if small {
+            load_icon_small(path)
+        } else {
+            load_icon(path)
+        }
+    } else if path.extension() == Some(OsStr::new("svg")) {
+        load_icon_svg(path, small)
+    } else {
+        None
+    }?;
+
+    if let Some(cache_path) = &cache_path_opt {
+        if let Err(err) = fs::write(cache_path, encode(&image, mtime)) {
+            log::warn!(
+                "failed to write icon cache {}: {}",
+                cache_path.display(),
+                err
+            );
+        }
+    }
+    Some(image)
+}
+
+/// Get the directory of the rasterized icon cache, creating it if needed
+fn disk_cache_dir() -> Option<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("launcher").ok()?;
+    match xdg_dirs.create_cache_directory("icons") {
+        Ok(ok) => Some(ok),
+        Err(err) => {
+            log::warn!("failed to create icon cache directory: {}", err);
+            None
+        }
+    }
+}
+
+/// Get the path of the rasterized icon cache file for an icon file at a size in pixels
+fn disk_cache_path(dir: &Path, path: &Path, size: u32) -> PathBuf {
+    // FNV-1a, which is stable between runs unlike the standard library hasher
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.as_os_str().as_encoded_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    dir.join(format!("{:016x}-{}.bin", hash, size))
+}
+
+/// Get the modification time of a file as seconds and nanoseconds since the epoch
+fn mtime_parts(mtime: SystemTime) -> (u64, u32) {
+    let duration = mtime.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+    (duration.as_secs(), duration.subsec_nanos())
+}
+
+/// Serialize a rasterized icon, with the modification time of the file it was loaded from
+fn encode(image: &Image, mtime: SystemTime) -> Vec<u8> {
+    let (secs, nanos) = mtime_parts(mtime);
+    let mut data = Vec::with_capacity(DISK_HEADER_LEN + image.data().len() * 4);
+    data.extend_from_slice(DISK_MAGIC);
+    data.extend_from_slice(&secs.to_le_bytes());
+    data.extend_from_slice(&nanos.to_le_bytes());
+    data.extend_from_slice(&image.width().to_le_bytes());
+    data.extend_from_slice(&image.height().to_le_bytes());
+    for color in image.data() {
+        data.extend_from_slice(&color.data.to_le_bytes());
+    }
+    data
+}
+
+/// Deserialize a rasterized icon, returns None if it is invalid or the file it was loaded from
+/// has a different modification time
+fn decode(data: &[u8], mtime: SystemTime) -> Option<Image> {
+    let header = data.get(..DISK_HEADER_LEN)?;
+    if &header[..8] != DISK_MAGIC {
+        return None;
+    }
+    let u32_at = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+    let secs = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    if (secs, u32_at(16)) != mtime_parts(mtime) {
+        return None;
+    }
+    let width = u32_at(20);
+    let height = u32_at(24);
+
+    let pixels = &data[DISK_HEADER_LEN..];
+    if pixels.len() != width as usize * height as usize * 4 {
+        return None;
+    }
+    let colors: Vec<Color> = pixels
+        .chunks_exact(4)
+        .map(|x| Color {
+            data: u32::from_le_bytes(x.try_into().unwrap()),
+        })
+        .collect();
+    Image::from_data(width, height, colors.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Image {
+        let mut image = Image::new(3, 2);
+        image.set(Color::rgba(1, 2, 3, 4));
+        image.pixel(1, 1, Color::rgb(0xff, 0, 0x80));
+        image
+    }
+
+    #[test]
+    fn round_trip() {
+        let mtime = UNIX_EPOCH + Duration::new(1_700_000_000, 123);
+        let image = image();
+        let decoded = decode(&encode(&image, mtime), mtime).unwrap();
+        assert_eq!(decoded.width(), 3);
+        assert_eq!(decoded.height(), 2);
+        assert!(decoded
+            .data()
+            .iter()
+            .zip(image.data())
+            .all(|(a, b)| a.data == b.data));
+    }
+
+    #[test]
+    fn stale_or_invalid() {
+        let mtime = UNIX_EPOCH + Duration::new(1_700_000_000, 123);
+        let data = encode(&image(), mtime);
+        assert!(decode(&data, mtime + Duration::from_secs(1)).is_none());
+        assert!(decode(&data[..data.len() - 1], mtime).is_none());
+        assert!(decode(&data[..4], mtime).is_none());
+        assert!(decode(b"not an icon cache file at all", mtime).is_none());
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/icons")
+            .join(name)
+    }
+
+    #[test]
+    fn shared() {
+        let source = IconSource::Path(fixture("terminal.svg"));
+        let a = icon(&source, true);
+        let b = icon(&source, true);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(a.width(), crate::icon_small_size() as u32);
+        assert!(!Rc::ptr_eq(&a, &icon(&source, false)));
+    }
+
+    #[test]
+    fn retried() {
+        let dir = std::env::temp_dir().join(format!("launcher-icon-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("late.svg");
+        let source = IconSource::Path(path.clone());
+
+        // An icon installed after its package is loaded the next time it is needed
+        let missing = icon(&source, true);
+        fs::copy(fixture("editor.svg"), &path).unwrap();
+        let installed = icon(&source, true);
+        let expected = icon(&IconSource::Path(fixture("editor.svg")), true);
+        assert!(!Rc::ptr_eq(&missing, &installed));
+        assert_eq!(installed.width(), expected.width());
+        assert!(installed
+            .data()
+            .iter()
+            .zip(expected.data())
+            .all(|(a, b)| a.data == b.data));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn disk_cache() {
+        let dir = std::env::temp_dir().join(format!("launcher-icons-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = fixture("terminal.svg");
+        let mtime = fs::metadata(&path).unwrap().modified().unwrap();
+        let size = crate::icon_small_size() as u32;
+        let cache_path = disk_cache_path(&dir, &path, size);
+
+        // Loading an icon writes it to the cache
+        let loaded = load_file(&path, size, mtime, true, Some(&dir)).unwrap();
+        let cached = decode(&fs::read(&cache_path).unwrap(), mtime).unwrap();
+        assert_eq!(cached.width(), loaded.width());
+        assert!(cached
+            .data()
+            .iter()
+            .zip(loaded.data())
+            .all(|(a, b)| a.data == b.data));
+
+        // An up to date cache file is used instead of the icon file
+        fs::write(&cache_path, encode(&image(), mtime)).unwrap();
+        let from_cache = load_file(&path, size, mtime, true, Some(&dir)).unwrap();
+        assert_eq!((from_cache.width(), from_cache.height()), (3, 2));
+
+        // A cache file from before the icon file changed is replaced
+        let later = mtime + Duration::from_secs(1);
+        let reloaded = load_file(&path, size, later, true, Some(&dir)).unwrap();
+        assert_eq!(reloaded.width(), loaded.width());
+        assert!(decode(&fs::read(&cache_path).unwrap(), later).is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use freedesktop_entry_parser::Entry;
+
+use crate::config::config_lines;
+
+/// The icon theme used when none is configured
+const DEFAULT_THEME: &str = "Cosmic";
+
+/// The theme that every icon theme falls back to
+const FALLBACK_THEME: &str = "hicolor";
+
+/// The icon used for packages whose icon cannot be found
+pub const FALLBACK_ICON: &str = "application-x-executable";
+
+lazy_static::lazy_static! {
+    static ref THEME_CHAIN: Vec<String> = theme_chain(&configured_theme(), &read_index);
+}
+
+/// Get the icon theme from the `theme` key of `launcher/icons`
+fn configured_theme() -> String {
+    let mut theme = DEFAULT_THEME.to_string();
+    for (key, value) in config_lines("icons") {
+        if key == "theme" && !value.is_empty() {
+            theme = value;
+        }
+    }
+    theme
+}
+
+/// The directories that icon themes are installed in, most important first
+fn icon_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".icons"));
+    }
+    if let Ok(xdg_dirs) = xdg::BaseDirectories::new() {
+        dirs.push(xdg_dirs.get_data_home().join("icons"));
+        for dir in xdg_dirs.get_data_dirs() {
+            dirs.push(dir.join("icons"));
+        }
+    }
+    dirs
+}
+
+/// Read the `index.theme` file of an installed icon theme
+fn read_index(theme: &str) -> Option<String> {
+    icon_dirs()
+        .into_iter()
+        .find_map(|dir| fs::read_to_string(dir.join(theme).join("index.theme")).ok())
+}
+
+/// Get the themes to search for icons in order, the theme followed by the themes it inherits
+/// from depth first, and finally `hicolor`
+///
+/// See https://specifications.freedesktop.org/icon-theme-spec/latest/#icon_lookup
+pub fn theme_chain(theme: &str, read_index: &dyn Fn(&str) -> Option<String>) -> Vec<String> {
+    fn visit(theme: &str, read_index: &dyn Fn(&str) -> Option<String>, chain: &mut Vec<String>) {
+        if theme == FALLBACK_THEME || chain.iter().any(|x| x == theme) {
+            return;
+        }
+        chain.push(theme.to_string());
+
+        let Some(index) = read_index(theme) else {
+            log::warn!("icon theme {} not found", theme);
+            return;
+        };
+        let parents = match Entry::parse(index) {
+            Ok(entry) => entry
+                .section("Icon Theme")
+                .attr("Inherits")
+                .unwrap_or_default()
+                .to_string(),
+            Err(err) => {
+                log::error!("failed to parse index of icon theme {}: {}", theme, err);
+                return;
+            }
+        };
+        for parent in parents.split(',') {
+            let parent = parent.trim();
+            if !parent.is_empty() {
+                visit(parent, read_index, chain);
+            }
+        }
+    }
+
+    let mut chain = Vec::new();
+    visit(theme, read_index, &mut chain);
+    chain.push(FALLBACK_THEME.to_string());
+    chain
+}
+
+/// Check if an icon path is inside a directory of an icon theme
+fn in_theme(path: &Path, theme: &str) -> bool {
+    let components: Vec<Component> = path.components().collect();
+    components.windows(2).any(|x| {
+        matches!(x[0].as_os_str().to_str(), Some("icons" | ".icons")) && x[1].as_os_str() == theme
+    })
+}
+
+/// Find an icon by name in the configured theme and the themes it inherits from
+pub fn lookup(name: &str, size: u16, scale: u16) -> Option<PathBuf> {
+    let find = |theme: &str| {
+        freedesktop_icons::lookup(name)
+            .with_size(size)
+            .with_scale(scale)
+            .with_theme(theme)
+            .find()
+    };
+
+    // Lookups fall back to hicolor and unthemed icons, which are only used when no theme in the
+    // chain has the icon
+    for theme in THEME_CHAIN.iter() {
+        match find(theme) {
+            Some(path) if in_theme(&path, theme) => return Some(path),
+            Some(_) => (),
+            None => return None,
+        }
+    }
+    find(FALLBACK_THEME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_index(theme: &str) -> Option<String> {
+        let inherits = match theme {
+            "Child" => "Parent,Other",
+            "Parent" => "Grandparent,hicolor",
+            "Grandparent" => "Child",
+            "Other" => "",
+            _ => return None,
+        };
+        Some(format!(
+            "[Icon Theme]\nName={}\nInherits={}\nDirectories=48x48/apps\n",
+            theme, inherits
+        ))
+    }
+
+    #[test]
+    fn chain() {
+        assert_eq!(
+            theme_chain("Child", &read_index),
+            vec!["Child", "Parent", "Grandparent", "Other", "hicolor"]
+        );
+        assert_eq!(theme_chain("Other", &read_index), vec!["Other", "hicolor"]);
+        assert_eq!(
+            theme_chain("Missing", &read_index),
+            vec!["Missing", "hicolor"]
+        );
+        assert_eq!(theme_chain("hicolor", &read_index), vec!["hicolor"]);
+    }
+
+    #[test]
+    fn theme_paths() {
+        let path = Path::new("/usr/share/icons/Cosmic/48x48/apps/files.png");
+        assert!(in_theme(path, "Cosmic"));
+        assert!(!in_theme(path, "hicolor"));
+        assert!(in_theme(
+            Path::new("/home/user/.icons/Custom/scalable/apps/files.svg"),
+            "Custom"
+        ));
+        assert!(!in_theme(
+            Path::new("/usr/share/pixmaps/files.png"),
+            "Cosmic"
+        ));
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use orbclient::*;
+
+use crate::config::{config_lines, config_mtimes};
+
+/// The config file holding super key bindings
+const CONFIG_NAME: &str = "keybindings";
+
+/// Something to do when a key binding is pressed
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Launch an exec string, like the Exec key of a desktop entry
+    Exec(String),
+    /// Open the start menu
+    StartMenu,
+    /// End the session
+    Logout,
+    /// Switch to the next keyboard layout
+    NextKeymap,
+    /// Open the run dialog
+    Run,
+}
+
+impl Action {
+    /// Parse an action, built-in actions start with `@`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "@start-menu" => Some(Action::StartMenu),
+            "@logout" => Some(Action::Logout),
+            "@next-keymap" => Some(Action::NextKeymap),
+            "@run" => Some(Action::Run),
+            _ if value.starts_with('@') => None,
+            _ if value.is_empty() => None,
+            _ => Some(Action::Exec(value.to_string())),
+        }
+    }
+}
+
+/// A key combination, the super key is always implied
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct KeyCombo {
+    pub shift: bool,
+    pub scancode: u8,
+}
+
+impl KeyCombo {
+    /// Parse a key combination like `Super+Shift+T`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut shift = false;
+        let mut scancode = None;
+        for part in value.split('+') {
+            match part.trim().to_ascii_lowercase().as_str() {
+                "super" | "meta" | "logo" => (),
+                "shift" => shift = true,
+                name => {
+                    if scancode.is_some() {
+                        return None;
+                    }
+                    scancode = Some(scancode_from_name(name)?);
+                }
+            }
+        }
+        Some(KeyCombo {
+            shift,
+            scancode: scancode?,
+        })
+    }
+}
+
+/// Get the scancode for a lowercase key name
+fn scancode_from_name(name: &str) -> Option<u8> {
+    Some(match name {
+        "a" => K_A,
+        "b" => K_B,
+        "c" => K_C,
+        "d" => K_D,
+        "e" => K_E,
+        "f" => K_F,
+        "g" => K_G,
+        "h" => K_H,
+        "i" => K_I,
+        "j" => K_J,
+        "k" => K_K,
+        "l" => K_L,
+        "m" => K_M,
+        "n" => K_N,
+        "o" => K_O,
+        "p" => K_P,
+        "q" => K_Q,
+        "r" => K_R,
+        "s" => K_S,
+        "t" => K_T,
+        "u" => K_U,
+        "v" => K_V,
+        "w" => K_W,
+        "x" => K_X,
+        "y" => K_Y,
+        "z" => K_Z,
+        "0" => K_0,
+        "1" => K_1,
+        "2" => K_2,
+        "3" => K_3,
+        "4" => K_4,
+        "5" => K_5,
+        "6" => K_6,
+        "7" => K_7,
+        "8" => K_8,
+        "9" => K_9,
+        "f1" => K_F1,
+        "f2" => K_F2,
+        "f3" => K_F3,
+        "f4" => K_F4,
+        "f5" => K_F5,
+        "f6" => K_F6,
+        "f7" => K_F7,
+        "f8" => K_F8,
+        "f9" => K_F9,
+        "f10" => K_F10,
+        "space" => K_SPACE,
+        "enter" | "return" => K_ENTER,
+        "tab" => K_TAB,
+        "esc" | "escape" => K_ESC,
+        "backspace" => K_BKSP,
+        "delete" | "del" => K_DEL,
+        "insert" | "ins" => K_INS,
+        "home" => K_HOME,
+        "end" => K_END,
+        "pageup" | "pgup" => K_PGUP,
+        "pagedown" | "pgdn" => K_PGDN,
+        "up" => K_UP,
+        "down" => K_DOWN,
+        "left" => K_LEFT,
+        "right" => K_RIGHT,
+        "print" | "prtsc" => K_PRTSC,
+        "minus" => K_MINUS,
+        "equals" => K_EQUALS,
+        "comma" => K_COMMA,
+        "period" => K_PERIOD,
+        "slash" => K_SLASH,
+        _ => return None,
+    })
+}
+
+/// Super key bindings, read from the system-wide and per-user `launcher/keybindings` files
+///
+/// Each line maps a key combination to an action, for example `Super+T=cosmic-term` or
+/// `Super+Space=@start-menu`. An empty action removes a binding.
+pub struct KeyBindings {
+    bindings: BTreeMap<KeyCombo, Action>,
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    shift: bool,
+}
+
+impl KeyBindings {
+    pub fn new() -> Self {
+        let mut key_bindings = KeyBindings {
+            bindings: BTreeMap::new(),
+            mtimes: Vec::new(),
+            shift: false,
+        };
+        key_bindings.load();
+        key_bindings
+    }
+
+    fn load(&mut self) {
+        self.load_lines(config_lines(CONFIG_NAME));
+        self.mtimes = config_mtimes(CONFIG_NAME);
+    }
+
+    /// Bind the defaults, then the `key=value` lines of the config files
+    fn load_lines(&mut self, lines: Vec<(String, String)>) {
+        self.bindings.clear();
+
+        // Defaults, used when no config file overrides them
+        for (key, value) in [
+            ("Super+B", "netsurf-fb"),
+            ("Super+F", "cosmic-files"),
+            ("Super+K", "@next-keymap"),
+            ("Super+R", "@run"),
+            ("Super+T", "cosmic-term"),
+        ] {
+            self.bind(key, value);
+        }
+
+        for (key, value) in lines {
+            self.bind(&key, &value);
+        }
+    }
+
+    fn bind(&mut self, key: &str, value: &str) {
+        let Some(combo) = KeyCombo::parse(key) else {
+            log::error!("invalid key binding {:?}", key);
+            return;
+        };
+        match Action::parse(value) {
+            Some(action) => {
+                self.bindings.insert(combo, action);
+            }
+            None => {
+                if !value.is_empty() {
+                    log::error!("invalid action {:?} for key binding {:?}", value, key);
+                }
+                self.bindings.remove(&combo);
+            }
+        }
+    }
+
+    /// Reload the bindings if a config file was added, removed, or modified
+    pub fn reload_if_changed(&mut self) {
+        if config_mtimes(CONFIG_NAME) != self.mtimes {
+            log::info!("reloading key bindings");
+            self.load();
+        }
+    }
+
+    /// Handle a super key event, returning the bound action when a binding is pressed
+    pub fn key(&mut self, key_event: &KeyEvent) -> Option<&Action> {
+        if key_event.scancode == K_LEFT_SHIFT || key_event.scancode == K_RIGHT_SHIFT {
+            self.shift = key_event.pressed;
+            return None;
+        }
+        if !key_event.pressed {
+            return None;
+        }
+        self.bindings.get(&KeyCombo {
+            shift: self.shift,
+            scancode: key_event.scancode,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::lines;
+
+    fn bindings(x: &[(&str, &str)]) -> KeyBindings {
+        let mut key_bindings = KeyBindings {
+            bindings: BTreeMap::new(),
+            mtimes: Vec::new(),
+            shift: false,
+        };
+        key_bindings.load_lines(lines(x));
+        key_bindings
+    }
+
+    fn combo(shift: bool, scancode: u8) -> KeyCombo {
+        KeyCombo { shift, scancode }
+    }
+
+    #[test]
+    fn key_combos() {
+        assert_eq!(KeyCombo::parse("Super+T"), Some(combo(false, K_T)));
+        assert_eq!(KeyCombo::parse("super+shift+t"), Some(combo(true, K_T)));
+        assert_eq!(
+            KeyCombo::parse("Shift + Meta + PgUp"),
+            Some(combo(true, K_PGUP))
+        );
+        // The super key is implied
+        assert_eq!(KeyCombo::parse("F4"), Some(combo(false, K_F4)));
+        assert_eq!(KeyCombo::parse("Super+Hyper"), None);
+        assert_eq!(KeyCombo::parse("Super+A+B"), None);
+        assert_eq!(KeyCombo::parse("Super+Shift"), None);
+    }
+
+    #[test]
+    fn actions() {
+        assert_eq!(Action::parse("@start-menu"), Some(Action::StartMenu));
+        assert_eq!(Action::parse("@logout"), Some(Action::Logout));
+        assert_eq!(Action::parse("@next-keymap"), Some(Action::NextKeymap));
+        assert_eq!(Action::parse("@run"), Some(Action::Run));
+        assert_eq!(Action::parse("@unknown"), None);
+        assert_eq!(Action::parse(""), None);
+        assert_eq!(
+            Action::parse("cosmic-term --maximize"),
+            Some(Action::Exec("cosmic-term --maximize".to_string()))
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        let key_bindings = bindings(&[
+            ("Super+T", "orbterm"),
+            ("Super+F", ""),
+            ("Super+Shift+Space", "@start-menu"),
+            ("Super+Hyper", "ignored"),
+            ("Super+B", "@unknown"),
+        ]);
+        let get = |shift, scancode| key_bindings.bindings.get(&combo(shift, scancode));
+        assert_eq!(get(false, K_T), Some(&Action::Exec("orbterm".to_string())));
+        assert_eq!(get(true, K_SPACE), Some(&Action::StartMenu));
+        assert_eq!(get(false, K_K), Some(&Action::NextKeymap));
+        // An empty or invalid action removes the default
+        assert_eq!(get(false, K_F), None);
+        assert_eq!(get(false, K_B), None);
+    }
+
+    #[test]
+    fn keys() {
+        let mut key_bindings = bindings(&[]);
+        let mut key = |scancode, pressed| {
+            key_bindings
+                .key(&KeyEvent {
+                    character: '\0',
+                    scancode,
+                    pressed,
+                })
+                .cloned()
+        };
+        assert_eq!(key(K_R, true), Some(Action::Run));
+        assert_eq!(key(K_R, false), None);
+        assert_eq!(key(K_LEFT_SHIFT, true), None);
+        assert_eq!(key(K_R, true), None);
+        assert_eq!(key(K_LEFT_SHIFT, false), None);
+        assert_eq!(
+            key(K_T, true),
+            Some(Action::Exec("cosmic-term".to_string()))
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use orbutils_shared::keymap::KeymapState;
+
+use crate::config::{config_lines, set_config_value};
+
+/// The config file holding the keyboard layout chosen on the bar
+const CONFIG_NAME: &str = "keyboard";
+
+/// The keymap state of the bar, shared with the keyboard applet
+pub type Keymaps = Rc<RefCell<KeymapState>>;
+
+/// Load the keymaps known to inputd and apply the layout saved in `launcher/keyboard`
+pub fn load_keymaps() -> Keymaps {
+    let mut keymap_state = KeymapState::new();
+    for (key, value) in config_lines(CONFIG_NAME) {
+        match key.as_str() {
+            "layout" => {
+                if keymap_state.list.contains(&value) {
+                    keymap_state.set_active(&value);
+                } else {
+                    log::error!("unknown keymap {:?}", value);
+                }
+            }
+            _ => log::error!("unknown keyboard setting {:?}", key),
+        }
+    }
+    Rc::new(RefCell::new(keymap_state))
+}
+
+/// Switch to a keymap and save it, returns true if it changed
+pub fn set_keymap(keymaps: &Keymaps, val: &str) -> bool {
+    if !keymaps.borrow_mut().set_active(val) {
+        return false;
+    }
+    if let Err(err) = set_config_value(CONFIG_NAME, "layout", val) {
+        log::error!("failed to save keymap {}: {}", val, err);
+    }
+    true
+}
+
+/// Switch to the next keymap and save it, returns true if it changed
+pub fn cycle_keymap(keymaps: &Keymaps) -> bool {
+    let next_opt = keymaps.borrow().next().map(|x| x.to_string());
+    match next_opt {
+        Some(next) => set_keymap(keymaps, &next),
+        None => false,
+    }
+}
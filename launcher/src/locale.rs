@@ -0,0 +1,355 @@
+use std::env;
+
+use freedesktop_entry_parser::AttrSelector;
+
+lazy_static::lazy_static! {
+    static ref LOCALES: Vec<String> = env_locales();
+}
+
+/// Translations of the labels created by the launcher, by language and then by English text
+const TRANSLATIONS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "de",
+        &[
+            ("Author", "Autor"),
+            ("Go back", "Zurück"),
+            ("Logout", "Abmelden"),
+            ("Restart", "Neu starten"),
+            ("Shutdown", "Herunterfahren"),
+            ("Cancel", "Abbrechen"),
+            ("Running applications", "Laufende Anwendungen"),
+            ("Pin to bar", "An Leiste anheften"),
+            ("Unpin from bar", "Von Leiste lösen"),
+            ("Show in file manager", "Im Dateimanager anzeigen"),
+            ("Clear notifications", "Benachrichtigungen löschen"),
+            ("Recent", "Zuletzt verwendet"),
+            ("Frequent", "Häufig verwendet"),
+            ("Session service stopped", "Sitzungsdienst beendet"),
+            ("MEM", "RAM"),
+            ("BAT", "AKKU"),
+            ("NET", "NETZ"),
+            ("KBD", "TAST"),
+            ("MSG", "NACHR"),
+            ("up", "an"),
+            ("down", "aus"),
+            (
+                "Always use this application",
+                "Immer diese Anwendung verwenden",
+            ),
+            ("Development", "Entwicklung"),
+            ("Education", "Bildung"),
+            ("Games", "Spiele"),
+            ("Graphics", "Grafik"),
+            ("Multimedia", "Multimedia"),
+            ("Network", "Netzwerk"),
+            ("Office", "Büro"),
+            ("Science", "Wissenschaft"),
+            ("Settings", "Einstellungen"),
+            ("System", "System"),
+            ("Utility", "Zubehör"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("Author", "Autor"),
+            ("Go back", "Volver"),
+            ("Logout", "Cerrar sesión"),
+            ("Restart", "Reiniciar"),
+            ("Shutdown", "Apagar"),
+            ("Cancel", "Cancelar"),
+            ("Running applications", "Aplicaciones en ejecución"),
+            ("Pin to bar", "Anclar a la barra"),
+            ("Unpin from bar", "Desanclar de la barra"),
+            ("Show in file manager", "Mostrar en el gestor de archivos"),
+            ("Clear notifications", "Borrar notificaciones"),
+            ("Recent", "Recientes"),
+            ("Frequent", "Frecuentes"),
+            ("Session service stopped", "Servicio de sesión detenido"),
+            ("NET", "RED"),
+            ("KBD", "TECL"),
+            ("MSG", "MSJ"),
+            ("up", "activa"),
+            ("down", "inactiva"),
+            (
+                "Always use this application",
+                "Usar siempre esta aplicación",
+            ),
+            ("Development", "Desarrollo"),
+            ("Education", "Educación"),
+            ("Games", "Juegos"),
+            ("Graphics", "Gráficos"),
+            ("Multimedia", "Multimedia"),
+            ("Network", "Red"),
+            ("Office", "Oficina"),
+            ("Science", "Ciencia"),
+            ("Settings", "Configuración"),
+            ("System", "Sistema"),
+            ("Utility", "Accesorios"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("Author", "Auteur"),
+            ("Go back", "Retour"),
+            ("Logout", "Se déconnecter"),
+            ("Restart", "Redémarrer"),
+            ("Shutdown", "Éteindre"),
+            ("Cancel", "Annuler"),
+            ("Running applications", "Applications en cours"),
+            ("Pin to bar", "Épingler à la barre"),
+            ("Unpin from bar", "Détacher de la barre"),
+            (
+                "Show in file manager",
+                "Afficher dans le gestionnaire de fichiers",
+            ),
+            ("Clear notifications", "Effacer les notifications"),
+            ("Recent", "Récents"),
+            ("Frequent", "Fréquents"),
+            ("Session service stopped", "Service de session arrêté"),
+            ("MEM", "MÉM"),
+            ("NET", "RÉS"),
+            ("KBD", "CLAV"),
+            ("up", "actif"),
+            ("down", "inactif"),
+            (
+                "Always use this application",
+                "Toujours utiliser cette application",
+            ),
+            ("Development", "Développement"),
+            ("Education", "Éducation"),
+            ("Games", "Jeux"),
+            ("Graphics", "Graphisme"),
+            ("Multimedia", "Multimédia"),
+            ("Network", "Réseau"),
+            ("Office", "Bureautique"),
+            ("Science", "Science"),
+            ("Settings", "Paramètres"),
+            ("System", "Système"),
+            ("Utility", "Accessoires"),
+        ],
+    ),
+    (
+        "it",
+        &[
+            ("Author", "Autore"),
+            ("Go back", "Indietro"),
+            ("Logout", "Esci"),
+            ("Restart", "Riavvia"),
+            ("Shutdown", "Spegni"),
+            ("Cancel", "Annulla"),
+            ("Running applications", "Applicazioni in esecuzione"),
+            ("Pin to bar", "Aggiungi alla barra"),
+            ("Unpin from bar", "Rimuovi dalla barra"),
+            ("Show in file manager", "Mostra nel gestore file"),
+            ("Clear notifications", "Cancella notifiche"),
+            ("Recent", "Recenti"),
+            ("Frequent", "Frequenti"),
+            ("Session service stopped", "Servizio di sessione arrestato"),
+            ("NET", "RETE"),
+            ("KBD", "TAST"),
+            ("up", "attiva"),
+            ("down", "inattiva"),
+            (
+                "Always use this application",
+                "Usa sempre questa applicazione",
+            ),
+            ("Development", "Sviluppo"),
+            ("Education", "Istruzione"),
+            ("Games", "Giochi"),
+            ("Graphics", "Grafica"),
+            ("Multimedia", "Multimedia"),
+            ("Network", "Rete"),
+            ("Office", "Ufficio"),
+            ("Science", "Scienza"),
+            ("Settings", "Impostazioni"),
+            ("System", "Sistema"),
+            ("Utility", "Accessori"),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            ("Author", "Autor"),
+            ("Go back", "Voltar"),
+            ("Logout", "Terminar sessão"),
+            ("Restart", "Reiniciar"),
+            ("Shutdown", "Encerrar"),
+            ("Cancel", "Cancelar"),
+            ("Running applications", "Aplicações em execução"),
+            ("Pin to bar", "Fixar na barra"),
+            ("Unpin from bar", "Desafixar da barra"),
+            ("Show in file manager", "Mostrar no gestor de ficheiros"),
+            ("Clear notifications", "Limpar notificações"),
+            ("Recent", "Recentes"),
+            ("Frequent", "Frequentes"),
+            ("Session service stopped", "Serviço de sessão parado"),
+            ("NET", "REDE"),
+            ("KBD", "TECL"),
+            ("up", "ligada"),
+            ("down", "desligada"),
+            ("Always use this application", "Usar sempre esta aplicação"),
+            ("Development", "Desenvolvimento"),
+            ("Education", "Educação"),
+            ("Games", "Jogos"),
+            ("Graphics", "Gráficos"),
+            ("Multimedia", "Multimédia"),
+            ("Network", "Rede"),
+            ("Office", "Escritório"),
+            ("Science", "Ciência"),
+            ("Settings", "Definições"),
+            ("System", "Sistema"),
+            ("Utility", "Acessórios"),
+        ],
+    ),
+    (
+        "pt_BR",
+        &[
+            ("Logout", "Sair"),
+            ("Shutdown", "Desligar"),
+            ("Running applications", "Aplicativos em execução"),
+            ("Show in file manager", "Mostrar no gerenciador de arquivos"),
+            ("Multimedia", "Multimídia"),
+            ("Settings", "Configurações"),
+        ],
+    ),
+];
+
+/// Get the locale names to look up for a locale, most specific first
+///
+/// A locale of the form `lang_COUNTRY.ENCODING@MODIFIER` matches `lang_COUNTRY@MODIFIER`,
+/// `lang_COUNTRY`, `lang@MODIFIER` and `lang`, as described in
+/// https://specifications.freedesktop.org/desktop-entry-spec/latest/localized-keys.html
+pub fn locale_names(locale: &str) -> Vec<String> {
+    let (locale, modifier_opt) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or(locale);
+    let (lang, country_opt) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut names = Vec::new();
+    if let (Some(country), Some(modifier)) = (country_opt, modifier_opt) {
+        names.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country_opt {
+        names.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier_opt {
+        names.push(format!("{}@{}", lang, modifier));
+    }
+    names.push(lang.to_string());
+    names
+}
+
+/// Get the locale names of the messages locale from `LC_ALL`, `LC_MESSAGES` or `LANG`
+fn env_locales() -> Vec<String> {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(locale) = env::var(var) {
+            if !locale.is_empty() {
+                return locale_names(&locale);
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// The locale names of the current locale, most specific first
+pub fn locales() -> &'static [String] {
+    &LOCALES
+}
+
+/// Get the value of a localized key for the given locale names, falling back to the
+/// unlocalized key
+pub fn localized_attr_in<'a, T: AsRef<str>>(
+    section: &'a AttrSelector<'a, T>,
+    key: &str,
+    locales: &[String],
+) -> Option<&'a str> {
+    locales
+        .iter()
+        .find_map(|locale| section.attr_with_param(key, locale))
+        .or_else(|| section.attr(key))
+}
+
+/// Get the value of a localized key for the current locale
+pub fn localized_attr<'a, T: AsRef<str>>(
+    section: &'a AttrSelector<'a, T>,
+    key: &str,
+) -> Option<&'a str> {
+    localized_attr_in(section, key, locales())
+}
+
+/// Translate a label created by the launcher for the given locale names
+pub fn translate_in(text: &str, locales: &[String]) -> String {
+    locales
+        .iter()
+        .find_map(|locale| {
+            let (_, translations) = TRANSLATIONS.iter().find(|(name, _)| name == locale)?;
+            let (_, translation) = translations.iter().find(|(key, _)| *key == text)?;
+            Some(translation.to_string())
+        })
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// Translate a label created by the launcher for the current locale
+pub fn translate(text: &str) -> String {
+    translate_in(text, locales())
+}
+
+#[cfg(test)]
+mod tests {
+    use freedesktop_entry_parser::Entry;
+
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            locale_names("sr_YU.UTF-8@Latn"),
+            vec!["sr_YU@Latn", "sr_YU", "sr@Latn", "sr"]
+        );
+        assert_eq!(locale_names("de_DE.UTF-8"), vec!["de_DE", "de"]);
+        assert_eq!(locale_names("fr"), vec!["fr"]);
+        assert!(locale_names("C.UTF-8").is_empty());
+        assert!(locale_names("POSIX").is_empty());
+    }
+
+    #[test]
+    fn attr() {
+        let entry = Entry::parse(
+            "[Desktop Entry]
+Name=Files
+Name[de]=Dateien
+Name[pt_BR]=Arquivos
+Comment=Browse files
+",
+        )
+        .unwrap();
+        let section = entry.section("Desktop Entry");
+        let attr = |key, locale| localized_attr_in(&section, key, &locale_names(locale));
+        assert_eq!(attr("Name", "de_AT.UTF-8"), Some("Dateien"));
+        assert_eq!(attr("Name", "pt_BR"), Some("Arquivos"));
+        assert_eq!(attr("Name", "pt_PT"), Some("Files"));
+        assert_eq!(attr("Name", "C"), Some("Files"));
+        assert_eq!(attr("Comment", "de_DE"), Some("Browse files"));
+        assert_eq!(attr("GenericName", "de_DE"), None);
+    }
+
+    #[test]
+    fn translations() {
+        let translate = |text, locale| translate_in(text, &locale_names(locale));
+        assert_eq!(translate("Go back", "de_DE.UTF-8"), "Zurück");
+        assert_eq!(translate("Logout", "pt_BR"), "Sair");
+        assert_eq!(translate("Go back", "pt_BR"), "Voltar");
+        assert_eq!(translate("Logout", "en_US"), "Logout");
+        assert_eq!(translate("Unknown label", "fr_FR"), "Unknown label");
+    }
+}
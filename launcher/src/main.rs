@@ -12,23 +12,72 @@ use libredox::flag;
 use log::{debug, error, info};
 use redox_log::{OutputBuilder, RedoxLogger};
 use std::collections::BTreeMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{ErrorKind, Read, Write};
 use std::os::unix::io::AsRawFd;
-use std::path::Path;
-use std::process::{Child, Command};
+use std::path::{Path, PathBuf};
+use std::process::Child;
 use std::sync::atomic::{AtomicIsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{env, io, mem};
 
 use orbclient::image::Image;
-use orbclient::{Color, EventOption, Renderer, Window, WindowFlag, K_ESC};
+use orbclient::{
+    Color, EventOption, Renderer, Window, WindowFlag, K_BKSP, K_DOWN, K_END, K_ENTER, K_ESC,
+    K_HOME, K_LEFT, K_PGDN, K_PGUP, K_RIGHT, K_TAB, K_UP,
+};
 use orbfont::Font;
-
-use package::{IconSource, Package};
-use theme::{BAR_COLOR, BAR_HIGHLIGHT_COLOR, TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
-
+use orbutils_shared::notification::Notification;
+
+use applet::{AppletAction, Applets};
+use autostart::Autostart;
+use calendar::Calendar;
+use clock::Clock;
+use config::{config_lines, set_config_value};
+use display::display_rects;
+use exec::{exec_takes_list, exec_to_command, file_manager_exec};
+use keybindings::{Action, KeyBindings};
+use keymap::{cycle_keymap, load_keymaps, Keymaps};
+use locale::translate;
+use mimeapps::MimeApps;
+use notification::NotificationServer;
+use package::{load_desktop_entries, IconSource, Package};
+use placement::{Placement, Rect};
+use run::{complete, executables, Completion, RunHistory};
+use service::Services;
+use session::SessionEnd;
+use theme::{BAR_COLOR, BAR_HIGHLIGHT_COLOR, FOCUS_COLOR, TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
+use toast::{Toast, MAX_TOASTS, TOAST_GAP};
+use tooltip::Tooltip;
+use usage::UsageStore;
+
+mod applet;
+mod autostart;
+mod calendar;
+mod clock;
+mod config;
+mod display;
+mod exec;
+mod icon_cache;
+mod icon_theme;
+mod keybindings;
+mod keymap;
+mod locale;
+mod mime;
+mod mimeapps;
+mod notification;
 mod package;
+mod placement;
+mod run;
+mod search;
+mod service;
+mod session;
+#[cfg(test)]
+mod snapshot;
 mod theme;
+mod toast;
+mod tooltip;
+mod usage;
 
 static SCALE: AtomicIsize = AtomicIsize::new(1);
 
@@ -36,6 +85,26 @@ fn chooser_width() -> u32 {
     200 * SCALE.load(Ordering::Relaxed) as u32
 }
 
+/// The maximum width of a tooltip, longer lines are wrapped
+fn tooltip_width() -> u32 {
+    320 * SCALE.load(Ordering::Relaxed) as u32
+}
+
+/// The maximum number of search results shown in the start menu
+const SEARCH_RESULTS: usize = 10;
+
+/// The number of packages in each of the Recent and Frequent sections of the start menu
+const USAGE_RESULTS: usize = 4;
+
+/// The number of rows moved by Page Up and Page Down in menus
+const PAGE_ROWS: i32 = 5;
+
+/// The number of steps the bar moves in when it is auto-hidden or shown
+const SLIDE_STEPS: i32 = 6;
+
+/// The time between the steps of the bar sliding
+const SLIDE_STEP_TIME: Duration = Duration::from_millis(15);
+
 fn font_size() -> i32 {
     16 * SCALE.load(Ordering::Relaxed) as i32
 }
@@ -54,40 +123,24 @@ static UI_PATH: &'static str = "/usr/share/ui";
 #[cfg(not(target_os = "redox"))]
 static UI_PATH: &'static str = "ui";
 
-fn exec_to_command(exec: &str, path_opt: Option<&str>) -> Option<Command> {
-    let args_vec: Vec<String> = shlex::split(exec)?;
-    let mut args = args_vec.iter();
-    let mut command = Command::new(args.next()?);
-    for arg in args {
-        if arg.starts_with('%') {
-            match arg.as_str() {
-                "%f" | "%F" | "%u" | "%U" => {
-                    if let Some(path) = &path_opt {
-                        command.arg(path);
-                    }
-                }
-                _ => {
-                    log::warn!("unsupported Exec code {:?} in {:?}", arg, exec);
-                    return None;
+fn spawn_exec(package: &Package, paths: &[&str]) {
+    // Applications that take a single file are launched once for each file
+    let path_lists: Vec<&[&str]> = if paths.len() > 1 && !exec_takes_list(&package.exec) {
+        paths.chunks(1).collect()
+    } else {
+        vec![paths]
+    };
+    for path_list in path_lists {
+        match exec_to_command(&package.exec, Some(package), path_list) {
+            Some(mut command) => match command.spawn() {
+                Ok(_) => {}
+                Err(err) => {
+                    error!("failed to launch {}: {}", package.exec, err);
                 }
+            },
+            None => {
+                error!("failed to parse {}", package.exec);
             }
-        } else {
-            command.arg(arg);
-        }
-    }
-    Some(command)
-}
-
-fn spawn_exec(exec: &str, path_opt: Option<&str>) {
-    match exec_to_command(exec, path_opt) {
-        Some(mut command) => match command.spawn() {
-            Ok(_) => {}
-            Err(err) => {
-                error!("failed to launch {}: {}", exec, err);
-            }
-        },
-        None => {
-            error!("failed to parse {}", exec);
         }
     }
 }
@@ -184,6 +237,44 @@ fn load_icon_svg<P: AsRef<Path>>(path: P, small: bool) -> Option<Image> {
     Some(size_icon(icon, small))
 }
 
+/// The directories that packages are loaded from, including ones that do not exist yet
+fn package_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(format!("{}/apps/", UI_PATH))];
+    if let Ok(xdg_dirs) = xdg::BaseDirectories::new() {
+        dirs.push(xdg_dirs.get_data_home().join("applications"));
+        for dir in xdg_dirs.get_data_dirs() {
+            dirs.push(dir.join("applications"));
+        }
+    }
+    dirs
+}
+
+/// The modification times of the package directories and the files in them, used to detect
+/// installed, removed and edited packages
+fn package_mtimes() -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mtime = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut mtimes = Vec::new();
+    for dir in package_dirs() {
+        // Editing an entry in place does not change the modification time of its directory
+        let mut files: Vec<PathBuf> = match dir.read_dir() {
+            Ok(read_dir) => read_dir
+                .filter_map(|entry_res| entry_res.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|x| x.is_file()))
+                .map(|entry| entry.path())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        files.sort();
+
+        mtimes.push((dir.clone(), mtime(&dir)));
+        for file in files {
+            let file_mtime = mtime(&file);
+            mtimes.push((file, file_mtime));
+        }
+    }
+    mtimes
+}
+
 fn get_packages() -> Vec<Package> {
     let mut packages: Vec<Package> = Vec::new();
 
@@ -204,98 +295,652 @@ fn get_packages() -> Vec<Package> {
     }
 
     if let Ok(xdg_dirs) = xdg::BaseDirectories::new() {
-        for path in xdg_dirs.find_data_files("applications") {
-            if let Ok(read_dir) = path.read_dir() {
-                for dir_entry_res in read_dir {
-                    let Ok(dir_entry) = dir_entry_res else {
-                        continue;
-                    };
-                    let Ok(id) = dir_entry.file_name().into_string() else {
-                        continue;
-                    };
-                    if let Some(package) = Package::from_desktop_entry(id, &dir_entry.path()) {
-                        packages.push(package);
+        let dirs: Vec<PathBuf> = xdg_dirs.find_data_files("applications").collect();
+        packages.extend(load_desktop_entries(&dirs));
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+/// What a bar shows, borrowed from the `Bar` so that it can be drawn onto any renderer
+struct BarContents<'a> {
+    start: Option<&'a Image>,
+    packages: &'a mut [Package],
+    /// The exec strings of the running children, packages with one are marked as running
+    running: &'a [&'a str],
+    font: &'a Font,
+    placement: &'a Placement,
+    applets: &'a Applets,
+    time: &'a str,
+    date: Option<&'a str>,
+}
+
+/// Where the parts of a bar were drawn, as distances along the bar
+struct BarLayout {
+    clock_start: i32,
+    applet_spans: Vec<(i32, i32)>,
+    /// The index of the selected package and where it starts, if a package is selected
+    selected_package: Option<(usize, i32)>,
+}
+
+/// Draw a bar on a screen onto a renderer the size of the bar, with the item at an index selected
+fn draw_bar<R: Renderer>(
+    renderer: &mut R,
+    contents: BarContents,
+    screen: Rect,
+    selected: i32,
+) -> BarLayout {
+    let (_, _, screen_w, screen_h) = screen;
+    let placement = contents.placement;
+    let font = contents.font;
+    renderer.set(BAR_COLOR);
+
+    let mut along = 0;
+    let mut i = 0;
+    let mut selected_package = None;
+
+    if let Some(start) = contents.start {
+        let (x, y) = placement.to_xy(along, 0);
+        if i == selected {
+            renderer.rect(x, y, start.width(), start.height(), BAR_HIGHLIGHT_COLOR);
+        }
+
+        renderer.image(x, y, start.width(), start.height(), start.data());
+
+        along += placement
+            .along_across(start.width() as i32, start.height() as i32)
+            .0;
+        i += 1;
+    }
+
+    for (package_i, package) in contents.packages.iter_mut().enumerate() {
+        let (x, y) = placement.to_xy(along, 0);
+        let image = package.icon.image();
+        if i == selected {
+            renderer.rect(x, y, image.width(), image.height(), BAR_HIGHLIGHT_COLOR);
+            selected_package = Some((package_i, along));
+        }
+
+        renderer.image(x, y, image.width(), image.height(), image.data());
+
+        if contents.running.contains(&package.exec.as_str()) {
+            let (ix, iy, iw, ih) = placement.indicator_rect(x, y, image.width(), image.height());
+            renderer.rect(ix, iy, iw, ih, TEXT_HIGHLIGHT_COLOR);
+        }
+
+        along += placement
+            .along_across(image.width() as i32, image.height() as i32)
+            .0;
+        i += 1;
+    }
+
+    let clock_start;
+    if placement.vertical() {
+        // Time and date are stacked and centered at the end of the bar
+        let mut texts = vec![(
+            font.render(contents.time, font_size() as f32),
+            TEXT_HIGHLIGHT_COLOR,
+        )];
+        if let Some(date) = contents.date {
+            texts.push((font.render(date, font_size() as f32), TEXT_COLOR));
+        }
+        let h: u32 = texts.iter().map(|(text, _)| text.height()).sum();
+        let mut y = screen_h as i32 - h as i32 - 8;
+        clock_start = y;
+        for (text, color) in texts {
+            let x = (icon_size() - text.width() as i32) / 2;
+            text.draw(renderer, x, y, color);
+            y += text.height() as i32;
+        }
+    } else if let Some(date) = contents.date {
+        // Time and date are stacked, with their right edges aligned
+        let text = font.render(contents.time, font_size() as f32);
+        let date_text = font.render(date, font_size() as f32);
+        let h = text.height() + date_text.height();
+        let mut y = (icon_size() - h as i32) / 2;
+        let x = screen_w as i32 - text.width() as i32 - 8;
+        text.draw(renderer, x, y, TEXT_HIGHLIGHT_COLOR);
+        y += text.height() as i32;
+        let date_x = screen_w as i32 - date_text.width() as i32 - 8;
+        date_text.draw(renderer, date_x, y, TEXT_COLOR);
+        clock_start = x.min(date_x);
+    } else {
+        let text = font.render(contents.time, (font_size() * 2) as f32);
+        let x = screen_w as i32 - text.width() as i32 - 8;
+        let y = (icon_size() - text.height() as i32) / 2;
+        text.draw(renderer, x, y, TEXT_HIGHLIGHT_COLOR);
+        clock_start = x;
+    }
+
+    // Applets are laid out in order, ending where the clock starts
+    let vertical = placement.vertical();
+    let lens: Vec<u32> = contents
+        .applets
+        .iter()
+        .map(|applet| {
+            if vertical {
+                applet.height(font_size())
+            } else {
+                applet.width(font, font_size())
+            }
+        })
+        .collect();
+    let mut along = clock_start - 8 - lens.iter().sum::<u32>() as i32;
+    let mut applet_spans = Vec::new();
+    for (applet, len) in contents.applets.iter().zip(lens) {
+        let (x, y) = placement.to_xy(along, 0);
+        let (w, h) = if vertical {
+            (icon_size() as u32, len)
+        } else {
+            (len, icon_size() as u32)
+        };
+        applet.draw(renderer, font, font_size(), (x, y, w, h));
+        applet_spans.push((along, along + len as i32));
+        along += len as i32;
+    }
+
+    BarLayout {
+        clock_start,
+        applet_spans,
+        selected_package,
+    }
+}
+
+fn draw_chooser_row<R: Renderer>(
+    renderer: &mut R,
+    font: &Font,
+    package: &mut Package,
+    y: i32,
+    selected: bool,
+) {
+    let w = renderer.width();
+
+    // Section headers have nothing to launch and are never highlighted
+    let selected = selected && !package.exec.is_empty();
+    if selected {
+        renderer.rect(0, y, w, icon_small_size() as u32, BAR_HIGHLIGHT_COLOR);
+        renderer.rounded_rect(0, y, w, icon_small_size() as u32, 4, false, FOCUS_COLOR);
+    }
+
+    let image = package.icon_small.image();
+    renderer.image(0, y, image.width(), image.height(), image.data());
+
+    font.render(&package.name, font_size() as f32).draw(
+        renderer,
+        icon_small_size() + 8,
+        y + 8,
+        if selected {
+            TEXT_HIGHLIGHT_COLOR
+        } else {
+            TEXT_COLOR
+        },
+    );
+}
+
+fn draw_chooser<R: Renderer>(
+    renderer: &mut R,
+    font: &Font,
+    packages: &mut Vec<Package>,
+    selected: i32,
+) {
+    renderer.set(BAR_COLOR);
+
+    let mut y = 0;
+    for (i, package) in packages.iter_mut().enumerate() {
+        draw_chooser_row(renderer, font, package, y, i as i32 == selected);
+        y += icon_small_size();
+    }
+
+    renderer.sync();
+}
+
+fn draw_search<R: Renderer>(
+    renderer: &mut R,
+    font: &Font,
+    query: &str,
+    packages: &mut [Package],
+    selected: i32,
+) {
+    renderer.set(BAR_COLOR);
+
+    let text = font.render(&format!("{}_", query), font_size() as f32);
+    text.draw(renderer, 8, 8, TEXT_HIGHLIGHT_COLOR);
+
+    let mut y = icon_small_size();
+    for (i, package) in packages.iter_mut().enumerate() {
+        draw_chooser_row(renderer, font, package, y, i as i32 == selected);
+        y += icon_small_size();
+    }
+
+    renderer.sync();
+}
+
+/// The current time in seconds since the epoch, as stored in the usage store
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Get the package at a selected index, if anything is selected
+fn list_get(packages: &[Package], selected: i32) -> Option<&Package> {
+    usize::try_from(selected).ok().and_then(|i| packages.get(i))
+}
+
+/// Move the selected index of a list with the keyboard, returns None if the key does not navigate
+///
+/// Section headers have nothing to launch, so they are skipped in the direction of the move.
+fn navigate(selected: i32, packages: &[Package], scancode: u8) -> Option<i32> {
+    let len = packages.len() as i32;
+    let last = len - 1;
+    if last < 0 {
+        return None;
+    }
+    let (target, step) = match scancode {
+        K_UP => (if selected <= 0 { last } else { selected - 1 }, -1),
+        K_DOWN => {
+            if selected < 0 || selected >= last {
+                (0, 1)
+            } else {
+                (selected + 1, 1)
+            }
+        }
+        K_HOME => (0, 1),
+        K_END => (last, -1),
+        K_PGUP => ((selected - PAGE_ROWS).max(0), -1),
+        K_PGDN => ((selected.max(0) + PAGE_ROWS).min(last), 1),
+        _ => return None,
+    };
+
+    let selectable = |i: &i32| !packages[*i as usize].exec.is_empty();
+    if matches!(scancode, K_UP | K_DOWN) {
+        // Up and down wrap around the list
+        (0..len)
+            .map(|n| (target + n * step).rem_euclid(len))
+            .find(selectable)
+    } else {
+        // Other moves stop at the ends, and turn back if there is nothing selectable beyond
+        (0..len)
+            .map(|n| target + n * step)
+            .chain((1..len).map(|n| target - n * step))
+            .filter(|i| (0..len).contains(i))
+            .find(selectable)
+    }
+}
+
+/// Show a borderless menu of packages, returning the exec string of the chosen one
+fn popup_menu(font: &Font, x: i32, y: i32, packages: &mut Vec<Package>) -> Option<String> {
+    let mut window = Window::new_flags(
+        x,
+        y,
+        chooser_width(),
+        packages.len() as u32 * icon_small_size() as u32,
+        "Menu",
+        &[WindowFlag::Borderless, WindowFlag::Transparent],
+    )
+    .unwrap();
+
+    let mut selected = -1;
+    let mut mouse_y = 0;
+    let mut mouse_left = false;
+    let mut last_mouse_left = false;
+    draw_chooser(&mut window, font, packages, selected);
+    'menu: loop {
+        for event in window.events() {
+            let redraw = match event.to_option() {
+                EventOption::Mouse(mouse_event) => {
+                    mouse_y = mouse_event.y;
+                    true
+                }
+                EventOption::Button(button_event) => {
+                    mouse_left = button_event.left;
+                    true
+                }
+                EventOption::Key(key_event) if key_event.pressed => {
+                    match key_event.scancode {
+                        K_ESC | K_LEFT => break 'menu,
+                        K_ENTER => {
+                            if let Some(package) = list_get(packages, selected) {
+                                return Some(package.exec.to_string());
+                            }
+                        }
+                        scancode => {
+                            if let Some(now_selected) = navigate(selected, packages, scancode) {
+                                if now_selected != selected {
+                                    selected = now_selected;
+                                    draw_chooser(&mut window, font, packages, selected);
+                                }
+                            }
+                        }
+                    }
+                    false
+                }
+                EventOption::Focus(focus_event) if !focus_event.focused => break 'menu,
+                EventOption::Quit(_) => break 'menu,
+                _ => false,
+            };
+
+            if redraw {
+                let now_selected = if mouse_y >= 0 {
+                    mouse_y / icon_small_size()
+                } else {
+                    -1
+                };
+                let now_selected = if now_selected < packages.len() as i32 {
+                    now_selected
+                } else {
+                    -1
+                };
+
+                if now_selected != selected {
+                    selected = now_selected;
+                    draw_chooser(&mut window, font, packages, selected);
+                }
+
+                if mouse_left && !last_mouse_left {
+                    if let Some(package) = list_get(packages, selected) {
+                        return Some(package.exec.to_string());
                     }
                 }
+
+                last_mouse_left = mouse_left;
             }
         }
     }
+    None
+}
+
+/// Create the context menu entries of a package: its desktop actions, pinning, and showing it
+fn context_packages(package: &Package, pinned: bool) -> Vec<Package> {
+    let mut packages = Vec::new();
+
+    for action in package.actions.iter() {
+        let mut action_package = package.clone();
+        action_package.name = action.name.clone();
+        action_package.exec = action.exec.clone();
+        packages.push(action_package);
+    }
+
+    packages.push({
+        let mut pin_package = Package::new();
+        pin_package.icon = package.icon.clone();
+        pin_package.icon_small = package.icon_small.clone();
+        if pinned {
+            pin_package.name = translate("Unpin from bar");
+            pin_package.exec = format!("unpin={}", package.id);
+        } else {
+            pin_package.name = translate("Pin to bar");
+            pin_package.exec = format!("pin={}", package.id);
+        }
+        pin_package
+    });
+
+    if let Some(dir) = package.desktop_path.as_ref().and_then(|x| x.parent()) {
+        packages.push({
+            let mut show_package = Package::new();
+            show_package.name = translate("Show in file manager");
+            let icon = format!("{}/icons/mimetypes/inode-directory.png", UI_PATH);
+            show_package.icon.source = IconSource::Path(icon.clone().into());
+            show_package.icon_small.source = IconSource::Path(icon.into());
+            show_package.exec = format!("show={}", dir.display());
+            show_package
+        });
+    }
 
-    packages.sort_by(|a, b| a.name.cmp(&b.name));
     packages
 }
 
-fn draw_chooser(window: &mut Window, font: &Font, packages: &mut Vec<Package>, selected: i32) {
-    let w = window.width();
+/// Read which packages are pinned to the bar, or unpinned from it, by ID
+fn pinned_packages() -> BTreeMap<String, bool> {
+    let mut pinned = BTreeMap::new();
+    for (id, value) in config_lines("pinned") {
+        pinned.insert(id, value == "true");
+    }
+    pinned
+}
 
-    window.set(BAR_COLOR);
+/// The bar on one display, with the state of the pointer over it
+struct BarDisplay {
+    /// The rectangle of the display on the screen
+    screen: Rect,
+    window: Window,
+    selected: i32,
+    selected_window: Window,
+    hidden: bool,
+    /// The position the bar is sliding from and the steps it has moved, while it slides
+    slide_opt: Option<((i32, i32), i32)>,
+    /// The distance along the bar where the clock starts
+    clock_start: i32,
+    /// The distances along the bar where each applet starts and ends
+    applet_spans: Vec<(i32, i32)>,
+    mouse_x: i32,
+    mouse_y: i32,
+    mouse_left: bool,
+    last_mouse_left: bool,
+    mouse_right: bool,
+    last_mouse_right: bool,
+}
 
-    let mut y = 0;
-    for (i, package) in packages.iter_mut().enumerate() {
-        if i as i32 == selected {
-            window.rect(0, y, w, icon_small_size() as u32, BAR_HIGHLIGHT_COLOR);
+impl BarDisplay {
+    fn new(screen: Rect, placement: &Placement) -> BarDisplay {
+        let (x, y, w, h) = if placement.auto_hide {
+            placement.hidden_rect(screen, icon_size() as u32)
+        } else {
+            placement.bar_rect(screen, icon_size() as u32)
+        };
+        let (screen_x, screen_y, screen_w, screen_h) = screen;
+        BarDisplay {
+            screen,
+            window: Window::new_flags(
+                x,
+                y,
+                w,
+                h,
+                "",
+                &[
+                    WindowFlag::Async,
+                    WindowFlag::Borderless,
+                    WindowFlag::Transparent,
+                ],
+            )
+            .expect("launcher: failed to open window"),
+            selected: -1,
+            selected_window: Window::new_flags(
+                screen_x,
+                screen_y + screen_h as i32,
+                screen_w,
+                (font_size() + 8) as u32,
+                "",
+                &[
+                    WindowFlag::Async,
+                    WindowFlag::Borderless,
+                    WindowFlag::Transparent,
+                ],
+            )
+            .expect("launcher: failed to open selected window"),
+            hidden: placement.auto_hide,
+            slide_opt: None,
+            clock_start: screen_w.max(screen_h) as i32,
+            applet_spans: Vec::new(),
+            mouse_x: -1,
+            mouse_y: -1,
+            mouse_left: false,
+            last_mouse_left: false,
+            mouse_right: false,
+            last_mouse_right: false,
         }
+    }
 
-        let image = package.icon_small.image();
-        window.image(0, y, image.width(), image.height(), image.data());
+    /// The rectangle of the bar on the screen, which is mostly off the screen when it is hidden
+    fn bar_rect(&self, placement: &Placement) -> Rect {
+        if self.hidden {
+            placement.hidden_rect(self.screen, icon_size() as u32)
+        } else {
+            placement.bar_rect(self.screen, icon_size() as u32)
+        }
+    }
 
-        font.render(&package.name, font_size() as f32).draw(
-            window,
-            icon_small_size() + 8,
-            y + 8,
-            if i as i32 == selected {
-                TEXT_HIGHLIGHT_COLOR
-            } else {
-                TEXT_COLOR
-            },
-        );
+    /// Move the tooltip of the selected package below the display
+    fn hide_selected_window(&mut self) {
+        let (x, y, _, h) = self.screen;
+        self.selected_window.set_pos(x, y + h as i32);
+    }
 
-        y += icon_small_size();
+    /// Move the bar onto a display that was moved or resized
+    fn set_screen(&mut self, placement: &Placement, screen: Rect) {
+        self.screen = screen;
+        let (x, y, w, h) = self.bar_rect(placement);
+        self.slide_opt = None;
+        self.window.set_pos(x, y);
+        self.window.set_size(w, h);
+        self.selected = -2; // Force bar redraw
+        self.hide_selected_window();
     }
 
-    window.sync();
+    /// Start sliding the bar off the screen or back onto it, if it is auto-hidden
+    ///
+    /// The bar is moved by `slide`, so events are handled while it slides.
+    fn set_hidden(&mut self, placement: &Placement, hidden: bool) {
+        if !placement.auto_hide || hidden == self.hidden {
+            return;
+        }
+        self.hidden = hidden;
+        // A bar that is already sliding turns back from where it is
+        self.slide_opt = Some(((self.window.x(), self.window.y()), 0));
+    }
+
+    /// Move a sliding bar one step, returns true if it is still sliding
+    fn slide(&mut self, placement: &Placement) -> bool {
+        let Some(((from_x, from_y), step)) = self.slide_opt else {
+            return false;
+        };
+        let step = step + 1;
+        let (to_x, to_y, _, _) = self.bar_rect(placement);
+        self.window.set_pos(
+            from_x + (to_x - from_x) * step / SLIDE_STEPS,
+            from_y + (to_y - from_y) * step / SLIDE_STEPS,
+        );
+        if step < SLIDE_STEPS {
+            self.slide_opt = Some(((from_x, from_y), step));
+            true
+        } else {
+            self.slide_opt = None;
+            false
+        }
+    }
 }
 
 struct Bar {
     children: Vec<(String, Child)>,
     packages: Vec<Package>,
+    search_packages: Vec<Package>,
     start: Option<Image>,
     start_packages: Vec<Package>,
     category_packages: BTreeMap<String, Vec<Package>>,
+    package_mtimes: Vec<(PathBuf, Option<SystemTime>)>,
     font: Font,
-    width: u32,
-    height: u32,
-    window: Window,
-    selected: i32,
-    selected_window: Window,
+    /// The bars on the displays chosen by `placement`
+    displays: Vec<BarDisplay>,
+    placement: Placement,
+    applets: Applets,
+    /// The keymaps known to inputd, shared with the keyboard applet so the key binding works
+    /// without it
+    keymaps: Keymaps,
+    /// The notifications shown next to the first bar, oldest first
+    toasts: Vec<Toast>,
+    /// The session services, which are restarted when they exit
+    services: Services,
+    usage: UsageStore,
+    clock: Clock,
     time: String,
+    date: Option<String>,
 }
 
 impl Bar {
-    fn new(width: u32, height: u32) -> Bar {
-        let all_packages = get_packages();
+    fn new() -> Bar {
+        let keymaps = load_keymaps();
+        let mut bar = Bar {
+            children: Vec::new(),
+            packages: Vec::new(),
+            search_packages: Vec::new(),
+            start: load_icon(&format!("{}/icons/places/start-here.png", UI_PATH)),
+            start_packages: Vec::new(),
+            category_packages: BTreeMap::new(),
+            package_mtimes: package_mtimes(),
+            font: Font::find(Some("Sans"), None, None).unwrap(),
+            displays: Vec::new(),
+            placement: Placement::new(),
+            applets: Applets::new(&keymaps),
+            keymaps,
+            toasts: Vec::new(),
+            services: Services::new(),
+            usage: UsageStore::load(),
+            clock: Clock::new(),
+            time: String::new(),
+            date: None,
+        };
+        bar.update_displays();
+        bar.load_packages();
+        bar
+    }
+
+    /// Open, move or close bars to match the connected displays, returns the number of bars that
+    /// were kept, the rest are new, and the closed bars, to unsubscribe them before they are
+    /// dropped
+    fn update_displays(&mut self) -> (usize, Vec<BarDisplay>) {
+        let screens = self.placement.select_displays(&display_rects());
+        let closed = self
+            .displays
+            .split_off(screens.len().min(self.displays.len()));
+        let kept = self.displays.len();
+        for (display_i, screen) in screens.into_iter().enumerate() {
+            match self.displays.get_mut(display_i) {
+                Some(display) => {
+                    if display.screen != screen {
+                        display.set_screen(&self.placement, screen);
+                    }
+                }
+                None => {
+                    info!("opening bar on display {} at {:?}", display_i, screen);
+                    self.displays.push(BarDisplay::new(screen, &self.placement));
+                }
+            }
+        }
+        (kept, closed)
+    }
+
+    /// Load the packages and sort them onto the bar and into the start menu categories
+    fn load_packages(&mut self) {
+        let mut all_packages = get_packages();
+        all_packages.retain(|package| !package.no_display);
+        let search_packages = all_packages.clone();
 
         // Handle packages with categories
+        let pinned = pinned_packages();
         let mut root_packages = Vec::new();
         let mut category_packages = BTreeMap::<String, Vec<Package>>::new();
         for package in all_packages {
-            if package.categories.is_empty() {
-                // Packages without a category go on the bar
-                root_packages.push(package);
-            } else {
-                // Packages with a category are collected
-                //TODO: since this clones the package, use an Arc to prevent icon reloads?
-                for category in package.categories.iter() {
-                    match category_packages.get_mut(category) {
-                        Some(packages) => {
-                            packages.push(package.clone());
-                        }
-                        None => {
-                            category_packages.insert(category.clone(), vec![package.clone()]);
-                        }
+            // Packages with a category are collected, clones share their icons
+            for category in package.categories.iter() {
+                match category_packages.get_mut(category) {
+                    Some(packages) => {
+                        packages.push(package.clone());
+                    }
+                    None => {
+                        category_packages.insert(category.clone(), vec![package.clone()]);
                     }
                 }
             }
+
+            // Packages without a category go on the bar, any package can be pinned or unpinned
+            let on_bar = match pinned.get(&package.id) {
+                Some(pinned) => *pinned,
+                None => package.categories.is_empty(),
+            };
+            if on_bar {
+                root_packages.push(package);
+            }
         }
 
         // Sort root packages by ID
@@ -306,7 +951,7 @@ impl Bar {
         for (category, packages) in category_packages.iter_mut() {
             start_packages.push({
                 let mut package = Package::new();
-                package.name = category.to_string();
+                package.name = translate(category);
                 let icon = format!("{}/icons/mimetypes/inode-directory.png", UI_PATH);
                 package.icon.source = IconSource::Path(icon.clone().into());
                 package.icon_small.source = IconSource::Path(icon.into());
@@ -316,7 +961,7 @@ impl Bar {
 
             packages.push({
                 let mut package = Package::new();
-                package.name = "Go back".to_string();
+                package.name = translate("Go back");
                 let icon = format!("{}/icons/mimetypes/inode-directory.png", UI_PATH);
                 package.icon.source = IconSource::Path(icon.clone().into());
                 package.icon_small.source = IconSource::Path(icon.into());
@@ -325,256 +970,1008 @@ impl Bar {
             });
         }
 
-        start_packages.push({
-            let mut package = Package::new();
-            package.name = "Logout".to_string();
-            let icon = format!("{}/icons/actions/system-log-out.png", UI_PATH);
-            package.icon.source = IconSource::Path(icon.clone().into());
-            package.icon_small.source = IconSource::Path(icon.into());
-            package.exec = "exit".to_string();
-            package
-        });
+        for end in [
+            SessionEnd::Logout,
+            SessionEnd::Restart,
+            SessionEnd::Shutdown,
+        ] {
+            start_packages.push(session_package(end, format!("session={}", end.name())));
+        }
 
-        Bar {
-            children: Vec::new(),
-            packages: root_packages,
-            start: load_icon(&format!("{}/icons/places/start-here.png", UI_PATH)),
-            start_packages,
-            category_packages,
-            font: Font::find(Some("Sans"), None, None).unwrap(),
-            width,
-            height,
-            window: Window::new_flags(
-                0,
-                height as i32 - icon_size(),
-                width,
-                icon_size() as u32,
-                "",
-                &[
-                    WindowFlag::Async,
-                    WindowFlag::Borderless,
-                    WindowFlag::Transparent,
-                ],
-            )
-            .expect("launcher: failed to open window"),
-            selected: -1,
-            selected_window: Window::new_flags(
-                0,
-                height as i32,
-                width,
-                (font_size() + 8) as u32,
-                "",
-                &[
-                    WindowFlag::Async,
-                    WindowFlag::Borderless,
-                    WindowFlag::Transparent,
-                ],
-            )
-            .expect("launcher: failed to open selected window"),
-            time: String::new(),
+        self.packages = root_packages;
+        self.search_packages = search_packages;
+        self.start_packages = start_packages;
+        self.category_packages = category_packages;
+    }
+
+    /// Reload the packages if a package directory or entry changed
+    fn reload_packages_if_changed(&mut self) {
+        let package_mtimes = package_mtimes();
+        if package_mtimes == self.package_mtimes {
+            return;
+        }
+        info!("packages changed, reloading packages");
+        self.package_mtimes = package_mtimes;
+        self.load_packages();
+        for display in self.displays.iter_mut() {
+            display.selected = -2; // Force bar redraw
         }
     }
 
+    /// Move every sliding bar one step, returns true if any is still sliding
+    fn slide(&mut self) -> bool {
+        let mut sliding = false;
+        for display in self.displays.iter_mut() {
+            sliding |= display.slide(&self.placement);
+        }
+        sliding
+    }
+
     fn update_time(&mut self) {
         let time = libredox::call::clock_gettime(flag::CLOCK_REALTIME)
             .expect("launcher: failed to read time");
 
-        let ts = time.tv_sec;
-        let s = ts % 86400;
-        let h = s / 3600;
-        let m = s / 60 % 60;
-        self.time = format!("{:>02}:{:>02}", h, m)
+        (self.time, self.date) = self.clock.format(time.tv_sec);
     }
 
-    fn draw(&mut self) {
-        self.window.set(BAR_COLOR);
-
-        let mut x = 0;
-        let mut y = 0;
-        let mut i = 0;
+    fn draw_all(&mut self) {
+        for display_i in 0..self.displays.len() {
+            self.draw(display_i);
+        }
+    }
 
-        if let Some(start) = self.start.as_ref() {
-            if i == self.selected {
-                self.window.rect(
-                    x as i32,
-                    y as i32,
-                    start.width() as u32,
-                    start.height() as u32,
-                    BAR_HIGHLIGHT_COLOR,
-                );
+    fn draw(&mut self, display_i: usize) {
+        let display = &mut self.displays[display_i];
+        let running: Vec<&str> = self
+            .children
+            .iter()
+            .map(|(exec, _)| exec.as_str())
+            .collect();
+        let layout = draw_bar(
+            &mut display.window,
+            BarContents {
+                start: self.start.as_ref(),
+                packages: &mut self.packages,
+                running: &running,
+                font: &self.font,
+                placement: &self.placement,
+                applets: &self.applets,
+                time: &self.time,
+                date: self.date.as_deref(),
+            },
+            display.screen,
+            display.selected,
+        );
+        display.clock_start = layout.clock_start;
+        display.applet_spans = layout.applet_spans;
+
+        if let Some((package_i, along)) = layout.selected_package {
+            let package = &self.packages[package_i];
+            let tooltip = Tooltip::new(&self.font, package, font_size(), tooltip_width());
+            let (tooltip_w, tooltip_h) = tooltip.size(&self.font);
+            if display.selected_window.width() != tooltip_w
+                || display.selected_window.height() != tooltip_h
+            {
+                display.selected_window.set_size(tooltip_w, tooltip_h);
             }
 
-            self.window
-                .image(x, y, start.width(), start.height(), start.data());
+            display.selected_window.set(Color::rgba(0, 0, 0, 0));
+            tooltip.draw(&mut display.selected_window, &self.font, 0, 0);
 
-            x += start.width() as i32;
-            i += 1;
+            display.selected_window.sync();
+            let bar_rect = display.bar_rect(&self.placement);
+            let (sw_x, sw_y) =
+                self.placement
+                    .popup_pos(bar_rect, along, tooltip_w, tooltip_h, display.screen);
+            display.selected_window.set_pos(sw_x, sw_y);
         }
 
-        for package in self.packages.iter_mut() {
-            if i == self.selected {
-                let image = package.icon.image();
-                self.window.rect(
-                    x as i32,
-                    y as i32,
-                    image.width() as u32,
-                    image.height() as u32,
-                    BAR_HIGHLIGHT_COLOR,
-                );
+        display.window.sync();
+    }
+
+    /// Get the Recent and Frequent sections at the top of the start menu, each after a header row
+    /// with no exec
+    fn usage_packages(&self) -> Vec<Package> {
+        let find = |id: &String| self.search_packages.iter().find(|x| &x.id == id);
+        let recent: Vec<&Package> = self
+            .usage
+            .recent(USAGE_RESULTS)
+            .iter()
+            .filter_map(find)
+            .collect();
+        let frequent: Vec<&Package> = self
+            .usage
+            .frequent(USAGE_RESULTS + recent.len())
+            .iter()
+            .filter_map(find)
+            .filter(|package| !recent.iter().any(|x| x.id == package.id))
+            .take(USAGE_RESULTS)
+            .collect();
+
+        let mut packages = Vec::new();
+        for (name, section) in [("Recent", recent), ("Frequent", frequent)] {
+            if section.is_empty() {
+                continue;
+            }
+            let mut header = Package::new();
+            header.name = translate(name);
+            packages.push(header);
+            packages.extend(section.into_iter().cloned());
+        }
+        packages
+    }
+
+    fn start_window(&mut self, display_i: usize, category_opt: Option<&String>) -> Option<String> {
+        let screen = self.displays[display_i].screen;
+        let bar_rect = self.displays[display_i].bar_rect(&self.placement);
+        let mut root_packages;
+        let packages = match category_opt {
+            Some(category) => self.category_packages.get_mut(category)?,
+            None => {
+                root_packages = self.usage_packages();
+                root_packages.extend(self.start_packages.iter().cloned());
+                &mut root_packages
+            }
+        };
+
+        let start_h = packages.len() as u32 * icon_small_size() as u32;
+        let (start_x, start_y) =
+            self.placement
+                .popup_pos(bar_rect, 0, chooser_width(), start_h, screen);
+        let mut start_window = Window::new_flags(
+            start_x,
+            start_y,
+            chooser_width(),
+            start_h,
+            "Start",
+            &[WindowFlag::Borderless, WindowFlag::Transparent],
+        )
+        .unwrap();
+
+        let mut selected = -1;
+        let mut mouse_y = 0;
+        let mut mouse_left = false;
+        let mut last_mouse_left = false;
+        let mut mouse_right = false;
+        let mut last_mouse_right = false;
+        let mut query = String::new();
+        let mut results: Vec<Package> = Vec::new();
+        // The tooltip is open as long as its window is kept
+        let mut _tooltip_window_opt: Option<Window> = None;
+        draw_chooser(&mut start_window, &self.font, packages, selected);
+        'start_choosing: loop {
+            for event in start_window.events() {
+                let mut query_changed = false;
+                let mut selection_changed = false;
+                let redraw = match event.to_option() {
+                    EventOption::Mouse(mouse_event) => {
+                        mouse_y = mouse_event.y;
+                        true
+                    }
+                    EventOption::Button(button_event) => {
+                        mouse_left = button_event.left;
+                        mouse_right = button_event.right;
+                        true
+                    }
+                    EventOption::TextInput(text_input_event) => {
+                        if !text_input_event.character.is_control() {
+                            query.push(text_input_event.character);
+                            query_changed = true;
+                        }
+                        false
+                    }
+                    EventOption::Key(key_event) if key_event.pressed => match key_event.scancode {
+                        K_ESC => {
+                            if query.is_empty() {
+                                break 'start_choosing;
+                            }
+                            query.clear();
+                            query_changed = true;
+                            false
+                        }
+                        K_BKSP => {
+                            query_changed = query.pop().is_some();
+                            false
+                        }
+                        K_ENTER => {
+                            // When searching, Enter launches the top hit if nothing is selected
+                            let package_opt = if query.is_empty() {
+                                list_get(packages, selected)
+                            } else {
+                                list_get(&results, selected.max(0))
+                            };
+                            if let Some(package) = package_opt.filter(|x| !x.exec.is_empty()) {
+                                return Some(package.exec.to_string());
+                            }
+                            false
+                        }
+                        K_RIGHT => {
+                            if query.is_empty() {
+                                if let Some(package) = list_get(packages, selected) {
+                                    if package.exec.starts_with("category=") {
+                                        return Some(package.exec.to_string());
+                                    }
+                                }
+                            }
+                            false
+                        }
+                        K_LEFT => {
+                            if query.is_empty() && category_opt.is_some() {
+                                return Some("exit".to_string());
+                            }
+                            false
+                        }
+                        scancode => {
+                            let list: &[Package] =
+                                if query.is_empty() { packages } else { &results };
+                            if let Some(now_selected) = navigate(selected, list, scancode) {
+                                selection_changed = now_selected != selected;
+                                selected = now_selected;
+                            }
+                            false
+                        }
+                    },
+                    EventOption::Focus(focus_event) => {
+                        if !focus_event.focused {
+                            break 'start_choosing;
+                        } else {
+                            false
+                        }
+                    }
+                    EventOption::Quit(_) => break 'start_choosing,
+                    _ => false,
+                };
 
-                self.selected_window.set(Color::rgba(0, 0, 0, 0));
+                if query_changed {
+                    results.clear();
+                    let now = unix_time();
+                    let boost = |package: &Package| self.usage.score(&package.id, now);
+                    for i in search::search(&self.search_packages, &query, boost)
+                        .into_iter()
+                        .take(SEARCH_RESULTS)
+                    {
+                        results.push(self.search_packages[i].clone());
+                    }
+
+                    let rows = if query.is_empty() {
+                        packages.len()
+                    } else {
+                        results.len() + 1
+                    };
+                    let h = rows as u32 * icon_small_size() as u32;
+                    let (x, y) = self
+                        .placement
+                        .popup_pos(bar_rect, 0, chooser_width(), h, screen);
+                    start_window.set_pos(x, y);
+                    start_window.set_size(chooser_width(), h);
+
+                    selected = -1;
+                    selection_changed = true;
+                }
+
+                if redraw {
+                    // When searching, the first row shows the query
+                    let (list, list_y) = if query.is_empty() {
+                        (&*packages, 0)
+                    } else {
+                        (&results, icon_small_size())
+                    };
+
+                    let mut now_selected = -1;
+
+                    let mut y = list_y;
+                    for (j, _package) in list.iter().enumerate() {
+                        if mouse_y >= y && mouse_y < y + icon_small_size() {
+                            now_selected = j as i32;
+                        }
+                        y += icon_small_size();
+                    }
+
+                    if now_selected != selected {
+                        selected = now_selected;
+                        selection_changed = true;
+                    }
 
-                let text = self.font.render(&package.name, font_size() as f32);
-                self.selected_window
-                    .rect(x, 0, text.width() + 8, text.height() + 8, BAR_COLOR);
-                text.draw(&mut self.selected_window, x + 4, 4, TEXT_HIGHLIGHT_COLOR);
+                    if mouse_left && !last_mouse_left {
+                        let mut y = list_y;
+                        for package_i in 0..list.len() {
+                            if mouse_y >= y
+                                && mouse_y < y + icon_small_size()
+                                && !list[package_i].exec.is_empty()
+                            {
+                                return Some(list[package_i].exec.to_string());
+                            }
+                            y += icon_small_size();
+                        }
+                    }
 
-                self.selected_window.sync();
-                let sw_y = self.window.y() - self.selected_window.height() as i32 - 4;
-                self.selected_window.set_pos(0, sw_y);
+                    if mouse_right && !last_mouse_right {
+                        // Categories, Go back, session entries and section headers have no
+                        // context menu
+                        if let Some(package) = list_get(list, selected).filter(|x| {
+                            !x.exec.is_empty()
+                                && !x.exec.starts_with("category=")
+                                && !x.exec.starts_with("session=")
+                                && x.exec != "exit"
+                        }) {
+                            let pinned = self.packages.iter().any(|x| x.id == package.id);
+                            let mut menu_packages = context_packages(package, pinned);
+                            return popup_menu(
+                                &self.font,
+                                self.placement.beside_x(
+                                    start_window.x(),
+                                    start_window.width(),
+                                    chooser_width(),
+                                ),
+                                start_window.y() + list_y + selected * icon_small_size(),
+                                &mut menu_packages,
+                            );
+                        }
+                    }
+
+                    last_mouse_left = mouse_left;
+                    last_mouse_right = mouse_right;
+                }
+
+                if selection_changed {
+                    let list_y = if query.is_empty() {
+                        draw_chooser(&mut start_window, &self.font, packages, selected);
+                        0
+                    } else {
+                        draw_search(
+                            &mut start_window,
+                            &self.font,
+                            &query,
+                            &mut results,
+                            selected,
+                        );
+                        icon_small_size()
+                    };
+
+                    // Show the details of the selected package next to the menu
+                    let list = if query.is_empty() {
+                        &*packages
+                    } else {
+                        &results
+                    };
+                    _tooltip_window_opt = list_get(list, selected)
+                        .map(|package| {
+                            Tooltip::new(&self.font, package, font_size(), tooltip_width())
+                        })
+                        .filter(|tooltip| tooltip.has_details())
+                        .and_then(|tooltip| {
+                            let (w, h) = tooltip.size(&self.font);
+                            let mut window = Window::new_flags(
+                                self.placement
+                                    .beside_x(start_window.x(), start_window.width(), w),
+                                start_window.y() + list_y + selected * icon_small_size(),
+                                w,
+                                h,
+                                "",
+                                &[
+                                    WindowFlag::Async,
+                                    WindowFlag::Borderless,
+                                    WindowFlag::Transparent,
+                                ],
+                            )?;
+                            tooltip.draw(&mut window, &self.font, 0, 0);
+                            window.sync();
+                            Some(window)
+                        });
+                }
             }
+        }
+        None
+    }
 
-            let image = package.icon.image();
+    /// Show a month calendar above the clock until it is closed
+    fn calendar_window(&mut self, display_i: usize) {
+        let mut calendar = Calendar::new(chrono::Local::now().date_naive());
+        let (w, h) = Calendar::size(font_size());
+        let display = &self.displays[display_i];
+        let (x, y) = self.placement.popup_pos(
+            display.bar_rect(&self.placement),
+            display.clock_start,
+            w,
+            h,
+            display.screen,
+        );
+        let mut calendar_window = Window::new_flags(
+            x,
+            y,
+            w,
+            h,
+            "Calendar",
+            &[WindowFlag::Borderless, WindowFlag::Transparent],
+        )
+        .unwrap();
 
-            self.window
-                .image(x, y, image.width(), image.height(), image.data());
+        let cell = Calendar::cell_size(font_size()) as i32;
+        let mut mouse_x = 0;
+        let mut mouse_y = 0;
+        calendar.draw(&mut calendar_window, &self.font, font_size());
+        'calendar: loop {
+            for event in calendar_window.events() {
+                let redraw = match event.to_option() {
+                    EventOption::Mouse(mouse_event) => {
+                        mouse_x = mouse_event.x;
+                        mouse_y = mouse_event.y;
+                        false
+                    }
+                    EventOption::Button(button_event) if button_event.left && mouse_y < cell => {
+                        // The arrows in the header change the month
+                        if mouse_x < cell {
+                            calendar.prev_month();
+                            true
+                        } else if mouse_x >= 6 * cell {
+                            calendar.next_month();
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    EventOption::Key(key_event) if key_event.pressed => match key_event.scancode {
+                        K_ESC => break 'calendar,
+                        K_LEFT | K_PGUP => {
+                            calendar.prev_month();
+                            true
+                        }
+                        K_RIGHT | K_PGDN => {
+                            calendar.next_month();
+                            true
+                        }
+                        K_HOME => {
+                            calendar = Calendar::new(calendar.today);
+                            true
+                        }
+                        _ => false,
+                    },
+                    EventOption::Focus(focus_event) if !focus_event.focused => break 'calendar,
+                    EventOption::Quit(_) => break 'calendar,
+                    _ => false,
+                };
 
-            let mut count = 0;
-            for (exec, _) in self.children.iter() {
-                if exec == &package.exec {
-                    count += 1;
+                if redraw {
+                    calendar.draw(&mut calendar_window, &self.font, font_size());
                 }
             }
-            if count > 0 {
-                self.window.rect(
-                    x as i32 + 4,
-                    y as i32,
-                    image.width() - 8,
-                    2,
-                    TEXT_HIGHLIGHT_COLOR,
-                );
+        }
+    }
+
+    /// Run the start menu until something is launched or it is closed, returns how the session
+    /// ends if that was chosen and confirmed
+    fn start_menu(&mut self, display_i: usize) -> Option<SessionEnd> {
+        let mut category_opt = None;
+        while let Some(exec) = self.start_window(display_i, category_opt.as_ref()) {
+            if exec.starts_with("category=") {
+                let category = &exec[9..];
+                category_opt = Some(category.to_string());
+            } else if exec == "exit" {
+                category_opt = None;
+            } else if let Some(end) = exec.strip_prefix("session=").and_then(SessionEnd::parse) {
+                if self.confirm_session_end(display_i, end) {
+                    return Some(end);
+                }
+                break;
+            } else {
+                self.menu_exec(exec);
+                break;
+            }
+        }
+        None
+    }
+
+    /// Ask whether to end the session, listing the running applications, in a menu in the
+    /// middle of a display
+    fn confirm_session_end(&mut self, display_i: usize, end: SessionEnd) -> bool {
+        let mut menu_packages = Vec::new();
+        if !self.children.is_empty() {
+            let mut header = Package::new();
+            header.name = translate("Running applications");
+            menu_packages.push(header);
+        }
+        for (exec, _) in self.children.iter() {
+            // Running applications are listed, but have nothing to choose
+            let mut package = match self.search_packages.iter().find(|x| &x.exec == exec) {
+                Some(package) => package.clone(),
+                None => {
+                    let mut package = Package::new();
+                    package.name = exec.clone();
+                    package
+                }
+            };
+            package.exec = String::new();
+            menu_packages.push(package);
+        }
+        menu_packages.push(session_package(end, "confirm".to_string()));
+        menu_packages.push({
+            let mut package = Package::new();
+            package.name = translate("Cancel");
+            package.exec = "cancel".to_string();
+            package
+        });
+
+        let (screen_x, screen_y, screen_w, screen_h) = self.displays[display_i].screen;
+        let h = menu_packages.len() as i32 * icon_small_size();
+        let x = screen_x + (screen_w as i32 - chooser_width() as i32) / 2;
+        let y = screen_y + (screen_h as i32 - h) / 2;
+        popup_menu(&self.font, x, y, &mut menu_packages).as_deref() == Some("confirm")
+    }
+
+    /// Show the context menu of a package on the bar, next to its icon `along` pixels along the bar
+    fn bar_context_menu(&mut self, display_i: usize, package_i: usize, along: i32) {
+        let mut menu_packages = context_packages(&self.packages[package_i], true);
+        let display = &self.displays[display_i];
+        let (x, y) = self.placement.popup_pos(
+            display.bar_rect(&self.placement),
+            along,
+            chooser_width(),
+            menu_packages.len() as u32 * icon_small_size() as u32,
+            display.screen,
+        );
+        if let Some(exec) = popup_menu(&self.font, x, y, &mut menu_packages) {
+            self.menu_exec(exec);
+        }
+    }
+
+    /// Show a notification and add it to the history, returns the window of its toast and the
+    /// toasts closed to make room for it, to unsubscribe them before they are dropped
+    fn notify(&mut self, notification: Notification) -> (Option<&Window>, Vec<Toast>) {
+        info!(
+            "notification from {:?}: {}",
+            notification.app_name, notification.summary
+        );
+        if self.applets.notify(&notification) {
+            self.draw_all();
+        }
+
+        let Some(toast) = Toast::new(
+            &notification,
+            &self.font,
+            font_size(),
+            tooltip_width(),
+            Instant::now(),
+        ) else {
+            return (None, Vec::new());
+        };
+        self.toasts.push(toast);
+        let excess = self.toasts.len().saturating_sub(MAX_TOASTS);
+        let closed = self.toasts.drain(..excess).collect();
+        self.layout_toasts();
+        (self.toasts.last().map(|toast| &toast.window), closed)
+    }
+
+    /// Stack the toasts at the far end of the first bar, the newest nearest to the bar
+    fn layout_toasts(&mut self) {
+        let Some(display) = self.displays.first() else {
+            return;
+        };
+        // Toasts stay clear of the bar even when it is hidden
+        let bar_rect = self.placement.bar_rect(display.screen, icon_size() as u32);
+        let mut offset = TOAST_GAP;
+        for toast in self.toasts.iter_mut().rev() {
+            let (w, h) = (toast.window.width(), toast.window.height());
+            let (x, y) = self
+                .placement
+                .stack_pos(bar_rect, w, h, offset, display.screen);
+            toast.window.set_pos(x, y);
+            offset += h as i32 + TOAST_GAP;
+        }
+    }
+
+    /// Close toasts that have expired, returns them to unsubscribe them before they are dropped
+    fn expire_toasts(&mut self, now: Instant) -> Vec<Toast> {
+        let (closed, kept): (Vec<Toast>, Vec<Toast>) = mem::take(&mut self.toasts)
+            .into_iter()
+            .partition(|toast| toast.expired(now));
+        self.toasts = kept;
+        if !closed.is_empty() {
+            self.layout_toasts();
+        }
+        closed
+    }
+
+    /// Handle the events of the toasts, which are closed when clicked, returns the closed toasts
+    /// to unsubscribe them before they are dropped
+    fn toast_events(&mut self) -> Vec<Toast> {
+        let mut closed = Vec::new();
+        for mut toast in mem::take(&mut self.toasts) {
+            let open = toast.window.events().all(|event| match event.to_option() {
+                EventOption::Button(button_event) => !button_event.left,
+                EventOption::Quit(_) => false,
+                _ => true,
+            });
+            if open {
+                self.toasts.push(toast);
+            } else {
+                closed.push(toast);
             }
-
-            x += image.width() as i32;
-            i += 1;
         }
-
-        let text = self.font.render(&self.time, (font_size() * 2) as f32);
-        x = self.width as i32 - text.width() as i32 - 8;
-        y = (icon_size() - text.height() as i32) / 2;
-        text.draw(&mut self.window, x, y, TEXT_HIGHLIGHT_COLOR);
-
-        self.window.sync();
+        if !closed.is_empty() {
+            self.layout_toasts();
+        }
+        closed
     }
 
-    fn start_window(&mut self, category_opt: Option<&String>) -> Option<String> {
-        let packages = match category_opt {
-            Some(category) => self.category_packages.get_mut(category)?,
-            None => &mut self.start_packages,
-        };
+    /// Show the run dialog on a display until a command line is run or it is closed
+    fn run_dialog(&mut self, display_i: usize) {
+        let mut history = RunHistory::load();
+        let executables = executables(&env::var_os("PATH").unwrap_or_default());
 
-        let start_h = packages.len() as u32 * icon_small_size() as u32;
-        let mut start_window = Window::new_flags(
-            0,
-            self.height as i32 - icon_size() - start_h as i32,
+        let (screen_x, screen_y, screen_w, screen_h) = self.displays[display_i].screen;
+        let mut window = Window::new_flags(
+            screen_x + (screen_w as i32 - chooser_width() as i32) / 2,
+            screen_y + screen_h as i32 / 4,
             chooser_width(),
-            start_h,
-            "Start",
+            icon_small_size() as u32,
+            "Run",
             &[WindowFlag::Borderless, WindowFlag::Transparent],
         )
         .unwrap();
 
+        let mut input = String::new();
+        let mut results: Vec<Package> = Vec::new();
         let mut selected = -1;
-        let mut mouse_y = 0;
         let mut mouse_left = false;
-        let mut last_mouse_left = false;
-        draw_chooser(&mut start_window, &self.font, packages, selected);
-        'start_choosing: loop {
-            for event in start_window.events() {
-                let redraw = match event.to_option() {
+        draw_search(&mut window, &self.font, &input, &mut results, selected);
+        let exec_opt = 'run: loop {
+            for event in window.events() {
+                let mut input_changed = false;
+                let mut redraw = false;
+                match event.to_option() {
                     EventOption::Mouse(mouse_event) => {
-                        mouse_y = mouse_event.y;
-                        true
+                        // The first row shows the command line
+                        let now_selected = mouse_event.y / icon_small_size() - 1;
+                        let now_selected = if now_selected < results.len() as i32 {
+                            now_selected
+                        } else {
+                            -1
+                        };
+                        redraw = now_selected != selected;
+                        selected = now_selected;
                     }
                     EventOption::Button(button_event) => {
+                        if button_event.left && !mouse_left {
+                            if let Some(package) = list_get(&results, selected) {
+                                break 'run Some(package.exec.clone());
+                            }
+                        }
                         mouse_left = button_event.left;
-                        true
                     }
-                    EventOption::Key(key_event) => match key_event.scancode {
-                        K_ESC => break 'start_choosing,
-                        _ => false,
+                    EventOption::TextInput(text_input_event)
+                        if !text_input_event.character.is_control() =>
+                    {
+                        input.push(text_input_event.character);
+                        input_changed = true;
+                    }
+                    EventOption::Key(key_event) if key_event.pressed => match key_event.scancode {
+                        K_ESC => break 'run None,
+                        K_BKSP => input_changed = input.pop().is_some(),
+                        K_TAB => {
+                            if let Some(package) = list_get(&results, selected.max(0)) {
+                                input = package.name.clone();
+                                input_changed = true;
+                            }
+                        }
+                        K_ENTER => {
+                            // Package names run the package, anything else is a command line
+                            let exec = match list_get(&results, selected) {
+                                Some(package) => package.exec.clone(),
+                                None => self
+                                    .search_packages
+                                    .iter()
+                                    .find(|x| x.name.to_lowercase() == input.to_lowercase())
+                                    .map_or(input.trim().to_string(), |x| x.exec.clone()),
+                            };
+                            if !exec.is_empty() {
+                                break 'run Some(exec);
+                            }
+                        }
+                        scancode => {
+                            if let Some(now_selected) = navigate(selected, &results, scancode) {
+                                redraw = now_selected != selected;
+                                selected = now_selected;
+                            }
+                        }
                     },
-                    EventOption::Focus(focus_event) => {
-                        if !focus_event.focused {
-                            break 'start_choosing;
-                        } else {
-                            false
+                    EventOption::Focus(focus_event) if !focus_event.focused => break 'run None,
+                    EventOption::Quit(_) => break 'run None,
+                    _ => (),
+                }
+
+                if input_changed {
+                    let package_names: Vec<&str> = self
+                        .search_packages
+                        .iter()
+                        .map(|x| x.name.as_str())
+                        .collect();
+                    results = complete(
+                        &input,
+                        &history.commands,
+                        &package_names,
+                        &executables,
+                        SEARCH_RESULTS,
+                    )
+                    .into_iter()
+                    .map(|completion| match completion {
+                        Completion::Package(i) => self.search_packages[i].clone(),
+                        Completion::History(command) | Completion::Executable(command) => {
+                            let mut package = Package::new();
+                            package.name = command.clone();
+                            package.exec = command;
+                            package
                         }
-                    }
-                    EventOption::Quit(_) => break 'start_choosing,
-                    _ => false,
-                };
+                    })
+                    .collect();
+                    window.set_size(
+                        chooser_width(),
+                        (results.len() as u32 + 1) * icon_small_size() as u32,
+                    );
+                    selected = -1;
+                    redraw = true;
+                }
 
                 if redraw {
-                    let mut now_selected = -1;
+                    draw_search(&mut window, &self.font, &input, &mut results, selected);
+                }
+            }
+        };
+        drop(window);
 
-                    let mut y = 0;
-                    for (j, _package) in packages.iter().enumerate() {
-                        if mouse_y >= y && mouse_y < y + icon_small_size() {
-                            now_selected = j as i32;
-                        }
-                        y += icon_small_size();
-                    }
+        if let Some(exec) = exec_opt {
+            // Packages are launched from their own entries, so only command lines are kept
+            if !self.search_packages.iter().any(|x| x.exec == exec) {
+                history.add(&exec);
+            }
+            self.spawn(exec);
+        }
+    }
 
-                    if now_selected != selected {
-                        selected = now_selected;
-                        draw_chooser(&mut start_window, &self.font, packages, selected);
-                    }
+    /// Switch to the next keyboard layout from a key binding
+    fn next_keymap(&mut self) {
+        if cycle_keymap(&self.keymaps) {
+            self.draw_all();
+        }
+    }
 
-                    if mouse_left && !last_mouse_left {
-                        let mut y = 0;
-                        for package_i in 0..packages.len() {
-                            if mouse_y >= y && mouse_y < y + icon_small_size() {
-                                return Some(packages[package_i].exec.to_string());
-                            }
-                            y += icon_small_size();
-                        }
+    /// Show the dropdown menu of an applet below or beside it
+    fn applet_menu(&mut self, display_i: usize, applet_i: usize, entries: Vec<String>) {
+        let mut menu_packages: Vec<Package> = entries
+            .into_iter()
+            .map(|entry| {
+                let mut package = Package::new();
+                package.name = entry.clone();
+                package.exec = entry;
+                package
+            })
+            .collect();
+        let display = &self.displays[display_i];
+        let along = display
+            .applet_spans
+            .get(applet_i)
+            .map_or(display.clock_start, |(start, _)| *start);
+        let (x, y) = self.placement.popup_pos(
+            display.bar_rect(&self.placement),
+            along,
+            chooser_width(),
+            menu_packages.len() as u32 * icon_small_size() as u32,
+            display.screen,
+        );
+        if let Some(entry) = popup_menu(&self.font, x, y, &mut menu_packages) {
+            if self.applets.menu_chosen(applet_i, &entry) {
+                self.draw_all();
+            }
+        }
+    }
+
+    /// Run an exec string chosen from a menu, handling the context menu entries
+    fn menu_exec(&mut self, exec: String) {
+        if let Some(id) = exec.strip_prefix("pin=") {
+            self.set_pinned(id, true);
+        } else if let Some(id) = exec.strip_prefix("unpin=") {
+            self.set_pinned(id, false);
+        } else if let Some(path) = exec.strip_prefix("show=") {
+            match file_manager_exec(path) {
+                Some(file_manager_exec) => self.spawn(file_manager_exec),
+                None => error!("failed to show {}", path),
+            }
+        } else {
+            self.spawn(exec);
+        }
+    }
+
+    /// Pin a package to the bar or unpin it, saving the choice in `launcher/pinned`
+    fn set_pinned(&mut self, id: &str, pinned: bool) {
+        if let Err(err) = set_config_value("pinned", id, if pinned { "true" } else { "false" }) {
+            error!("failed to save pinned {}: {}", id, err);
+        }
+
+        if pinned {
+            if !self.packages.iter().any(|x| x.id == id) {
+                if let Some(package) = self.search_packages.iter().find(|x| x.id == id) {
+                    self.packages.push(package.clone());
+                }
+            }
+        } else {
+            self.packages.retain(|x| x.id != id);
+        }
+        for display in self.displays.iter_mut() {
+            display.selected = -2; // Force bar redraw
+        }
+        self.draw_all();
+    }
+
+    /// Select and click items under the pointer on a bar, returns how the session ends if that
+    /// was chosen from the start menu
+    fn pointer_event(&mut self, display_i: usize) -> Option<SessionEnd> {
+        let display = &mut self.displays[display_i];
+        let mut now_selected = -1;
+        let mut now_selected_along = 0;
+        let (mouse_along, mouse_across) = self
+            .placement
+            .along_across(display.mouse_x, display.mouse_y);
+
+        if let Some(start) = self.start.as_ref() {
+            let mut along = 0;
+            let mut i = 0;
+
+            {
+                let (len, _) = self
+                    .placement
+                    .along_across(start.width() as i32, start.height() as i32);
+                if mouse_across >= 0 && mouse_along >= along && mouse_along < along + len {
+                    now_selected = i;
+                }
+                along += len;
+                i += 1;
+            }
+
+            for package in self.packages.iter_mut() {
+                let image = package.icon.image();
+                let (len, _) = self
+                    .placement
+                    .along_across(image.width() as i32, image.height() as i32);
+                if mouse_across >= 0 && mouse_along >= along && mouse_along < along + len {
+                    now_selected = i;
+                    now_selected_along = along;
+                }
+                along += len;
+                i += 1;
+            }
+        }
+
+        if now_selected != display.selected {
+            display.selected = now_selected;
+            display.hide_selected_window();
+            self.draw(display_i);
+        }
+
+        let display = &mut self.displays[display_i];
+        let clicked = display.mouse_left && !display.last_mouse_left;
+        let right_clicked = display.mouse_right && !display.last_mouse_right;
+        display.last_mouse_left = display.mouse_left;
+        display.last_mouse_right = display.mouse_right;
+        let selected = display.selected;
+        let clock_start = display.clock_start;
+        let applet_i_opt = display
+            .applet_spans
+            .iter()
+            .position(|(start, end)| mouse_along >= *start && mouse_along < *end);
+
+        if clicked {
+            let mut i = 0;
+
+            if i == selected {
+                if let Some(end) = self.start_menu(display_i) {
+                    return Some(end);
+                }
+            }
+            i += 1;
+
+            if mouse_across >= 0 && mouse_along >= clock_start {
+                self.calendar_window(display_i);
+            }
+
+            if let Some(applet_i) = applet_i_opt.filter(|_| mouse_across >= 0) {
+                match self.applets.click(applet_i) {
+                    Some(AppletAction::Exec(exec)) => self.spawn(exec),
+                    Some(AppletAction::Menu(entries)) => {
+                        self.applet_menu(display_i, applet_i, entries)
                     }
+                    None => (),
+                }
+            }
 
-                    last_mouse_left = mouse_left;
+            for package_i in 0..self.packages.len() {
+                if i == selected {
+                    let exec = self.packages[package_i].exec.clone();
+                    self.spawn(exec);
                 }
+                i += 1;
+            }
+        }
+
+        if right_clicked {
+            // Packages start after the start button
+            let package_i = selected - 1;
+            if package_i >= 0 && (package_i as usize) < self.packages.len() {
+                self.bar_context_menu(display_i, package_i as usize, now_selected_along);
             }
         }
+
         None
     }
 
     fn spawn(&mut self, exec: String) {
-        match exec_to_command(&exec, None) {
+        // Launches from the bar and menus use the settings of the package they came from
+        let package_opt = self
+            .search_packages
+            .iter()
+            .find(|x| x.exec == exec || x.actions.iter().any(|action| action.exec == exec));
+        let command_opt = exec_to_command(&exec, package_opt, &[]);
+        let id_opt = package_opt.map(|package| package.id.clone());
+        match command_opt {
             Some(mut command) => match command.spawn() {
                 Ok(child) => {
+                    if let Some(id) = id_opt {
+                        self.usage.record(&id, unix_time());
+                    }
                     self.children.push((exec, child));
                     //TODO: should redraw be done here?
-                    self.draw();
+                    self.draw_all();
                 }
                 Err(err) => error!("failed to spawn {}: {}", exec, err),
             },
             None => error!("failed to parse {}", exec),
         }
     }
-}
 
-fn bar_main(width: u32, height: u32) -> io::Result<()> {
-    let mut bar = Bar::new(width, height);
+    /// Launch a package from an autostart entry, tracked like packages launched from the bar
+    fn autostart(&mut self, package: &Package) {
+        match exec_to_command(&package.exec, Some(package), &[]) {
+            Some(mut command) => match command.spawn() {
+                Ok(child) => {
+                    info!("autostarted {} ({})", package.id, child.id());
+                    self.children.push((package.exec.clone(), child));
+                }
+                Err(err) => error!("failed to autostart {}: {}", package.id, err),
+            },
+            None => error!("failed to parse {}", package.exec),
+        }
+    }
+}
 
-    match Command::new("background").spawn() {
-        Ok(child) => bar.children.push(("background".to_string(), child)),
-        Err(err) => error!("failed to launch background: {}", err),
+/// Make a timer of the time scheme fire after a duration
+fn set_timer(time_file: &mut File, after: Duration) -> io::Result<()> {
+    let mut time_buf = [0_u8; mem::size_of::<TimeSpec>()];
+    if time_file.read(&mut time_buf)? < mem::size_of::<TimeSpec>() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "failed to read time",
+        ));
     }
 
+    let time = libredox::data::timespec_from_mut_bytes(&mut time_buf);
+    let nsec = time.tv_nsec as u64 + after.subsec_nanos() as u64;
+    time.tv_sec += (after.as_secs() + nsec / 1_000_000_000) as libc::time_t;
+    time.tv_nsec = (nsec % 1_000_000_000) as libc::c_long;
+    time_file.write_all(&time_buf)
+}
+
+fn bar_main() -> io::Result<()> {
+    let mut bar = Bar::new();
+    let mut key_bindings = KeyBindings::new();
+
+    // Services that could not be started are notified once the bar windows are subscribed
+    let mut notifications = bar.services.start(Instant::now());
+    let mut autostart = Autostart::new(Instant::now());
+
     user_data! {
         enum Event {
             Time,
+            Slide,
             Window,
+            Notification,
         }
     }
     let event_queue = EventQueue::<Event>::new().expect("launcher: failed to create event queue");
@@ -588,24 +1985,81 @@ fn bar_main(width: u32, height: u32) -> io::Result<()> {
             event::EventFlags::READ,
         )
         .expect("launcher: failed to subscribe to timer");
+
+    // Auto-hidden bars slide on a timer of their own, which runs only while a bar slides
+    let mut slide_file = File::open(format!("/scheme/time/{}", flag::CLOCK_MONOTONIC))?;
     event_queue
         .subscribe(
-            bar.window.as_raw_fd() as usize,
-            Event::Window,
+            slide_file.as_raw_fd() as usize,
+            Event::Slide,
             event::EventFlags::READ,
         )
-        .expect("launcher: failed to subscribe to timer");
+        .expect("launcher: failed to subscribe to slide timer");
+    let mut sliding = false;
+
+    let subscribe_window = |window: &Window| {
+        event_queue
+            .subscribe(
+                window.as_raw_fd() as usize,
+                Event::Window,
+                event::EventFlags::READ,
+            )
+            .expect("launcher: failed to subscribe to window");
+    };
+    let unsubscribe_window = |window: &Window| {
+        if let Err(err) = event_queue.unsubscribe(window.as_raw_fd() as usize) {
+            error!("failed to unsubscribe from window: {}", err);
+        }
+    };
+    let close_toasts = |toasts: Vec<Toast>| {
+        for toast in toasts {
+            unsubscribe_window(&toast.window);
+        }
+    };
+    let show_notification = |bar: &mut Bar, notification: Notification| {
+        let (window_opt, closed) = bar.notify(notification);
+        if let Some(window) = window_opt {
+            subscribe_window(window);
+        }
+        close_toasts(closed);
+    };
+    for display in bar.displays.iter() {
+        subscribe_window(&display.window);
+    }
+    for notification in notifications.drain(..) {
+        show_notification(&mut bar, notification);
+    }
 
-    let mut mouse_x = -1;
-    let mut mouse_y = -1;
-    let mut mouse_left = false;
-    let mut last_mouse_left = false;
+    let mut notification_server_opt = match NotificationServer::bind() {
+        Ok(server) => {
+            event_queue
+                .subscribe(
+                    server.as_raw_fd() as usize,
+                    Event::Notification,
+                    event::EventFlags::READ,
+                )
+                .expect("launcher: failed to subscribe to notifications");
+            Some(server)
+        }
+        Err(err) => {
+            error!("failed to start notification server: {}", err);
+            None
+        }
+    };
+
+    // The display the pointer was last on, where the start menu opens from key bindings
+    let mut active_display = 0;
+
+    // The session is logged out if the bar closes without choosing another way
+    let mut session_end = SessionEnd::Logout;
 
     let all_events = [Event::Time, Event::Window].into_iter();
 
-    'events: for event in all_events
-        .chain(event_queue.map(|e| e.expect("launcher: failed to get next event").user_data))
-    {
+    'events: for event in all_events.chain(
+        event_queue
+            .iter()
+            .map(|e| e.expect("launcher: failed to get next event").user_data),
+    ) {
         match event {
             Event::Time => {
                 let mut time_buf = [0_u8; core::mem::size_of::<TimeSpec>()];
@@ -643,16 +2097,26 @@ fn bar_main(width: u32, height: u32) -> io::Result<()> {
                     }
                 }
 
-                loop {
-                    let mut status = 0;
-                    let pid = wait(&mut status)?;
-                    if pid == 0 {
-                        break;
+                for notification in bar.services.supervise(Instant::now()) {
+                    show_notification(&mut bar, notification);
+                }
+                for package in autostart.due(Instant::now()) {
+                    bar.autostart(&package);
+                }
+                // Finish reading notifications from clients that were slow to send them
+                if let Some(server) = &mut notification_server_opt {
+                    for notification in server.accept() {
+                        show_notification(&mut bar, notification);
                     }
                 }
 
+                key_bindings.reload_if_changed();
+                bar.reload_packages_if_changed();
+
                 bar.update_time();
-                bar.draw();
+                bar.applets.update(Instant::now());
+                close_toasts(bar.expire_toasts(Instant::now()));
+                bar.draw_all();
 
                 match libredox::data::timespec_from_mut_bytes(&mut time_buf) {
                     time => {
@@ -662,166 +2126,141 @@ fn bar_main(width: u32, height: u32) -> io::Result<()> {
                 }
                 time_file.write(&time_buf)?;
             }
+            Event::Slide => {
+                sliding = bar.slide();
+                if sliding {
+                    set_timer(&mut slide_file, SLIDE_STEP_TIME)?;
+                }
+            }
+            Event::Notification => {
+                if let Some(server) = &mut notification_server_opt {
+                    for notification in server.accept() {
+                        show_notification(&mut bar, notification);
+                    }
+                }
+            }
             Event::Window => {
-                for event in bar.window.events() {
-                    //TODO: remove hack for super event
-                    if event.code >= 0x1000_0000 {
-                        let mut super_event = event;
-                        super_event.code -= 0x1000_0000;
-
-                        //TODO: configure super keybindings
-                        let event_option = super_event.to_option();
-                        debug!("launcher: super {:?}", event_option);
-                        match event_option {
-                            EventOption::Key(key_event) => match key_event.scancode {
-                                orbclient::K_B => {
-                                    if key_event.pressed {
-                                        bar.spawn("netsurf-fb".to_string());
-                                    }
-                                }
-                                orbclient::K_F => {
-                                    if key_event.pressed {
-                                        bar.spawn("cosmic-files".to_string());
-                                    }
-                                }
-                                orbclient::K_T => {
-                                    if key_event.pressed {
-                                        bar.spawn("cosmic-term".to_string());
+                close_toasts(bar.toast_events());
+
+                // Events of every bar are read, since the queue does not tell which one is ready
+                let mut display_i = 0;
+                while display_i < bar.displays.len() {
+                    let events = bar.displays[display_i].window.events();
+                    for event in events {
+                        //TODO: remove hack for super event
+                        if event.code >= 0x1000_0000 {
+                            let mut super_event = event;
+                            super_event.code -= 0x1000_0000;
+
+                            let event_option = super_event.to_option();
+                            debug!("launcher: super {:?}", event_option);
+                            match event_option {
+                                EventOption::Key(key_event) => {
+                                    match key_bindings.key(&key_event).cloned() {
+                                        Some(Action::Exec(exec)) => bar.spawn(exec),
+                                        Some(Action::StartMenu) => {
+                                            let display_i =
+                                                active_display.min(bar.displays.len() - 1);
+                                            if let Some(end) = bar.start_menu(display_i) {
+                                                session_end = end;
+                                                break 'events;
+                                            }
+                                        }
+                                        Some(Action::Logout) => {
+                                            let display_i =
+                                                active_display.min(bar.displays.len() - 1);
+                                            if bar
+                                                .confirm_session_end(display_i, SessionEnd::Logout)
+                                            {
+                                                break 'events;
+                                            }
+                                        }
+                                        Some(Action::NextKeymap) => bar.next_keymap(),
+                                        Some(Action::Run) => {
+                                            let display_i =
+                                                active_display.min(bar.displays.len() - 1);
+                                            bar.run_dialog(display_i);
+                                        }
+                                        None => (),
                                     }
                                 }
                                 _ => (),
-                            },
-                            _ => (),
-                        }
-
-                        continue;
-                    }
+                            }
 
-                    let redraw = match event.to_option() {
-                        EventOption::Mouse(mouse_event) => {
-                            mouse_x = mouse_event.x;
-                            mouse_y = mouse_event.y;
-                            true
-                        }
-                        EventOption::Button(button_event) => {
-                            mouse_left = button_event.left;
-                            true
-                        }
-                        EventOption::Screen(screen_event) => {
-                            bar.width = screen_event.width;
-                            bar.height = screen_event.height;
-                            bar.window
-                                .set_pos(0, screen_event.height as i32 - icon_size());
-                            bar.window.set_size(screen_event.width, icon_size() as u32);
-                            bar.selected = -2; // Force bar redraw
-                            bar.selected_window.set_pos(0, screen_event.height as i32);
-                            bar.selected_window
-                                .set_size(screen_event.width, (font_size() + 8) as u32);
-                            true
+                            continue;
                         }
-                        EventOption::Hover(hover_event) => {
-                            if hover_event.entered {
-                                false
-                            } else {
-                                mouse_x = -1;
-                                mouse_y = -1;
+
+                        let display = &mut bar.displays[display_i];
+                        let redraw = match event.to_option() {
+                            EventOption::Mouse(mouse_event) => {
+                                display.mouse_x = mouse_event.x;
+                                display.mouse_y = mouse_event.y;
+                                active_display = display_i;
                                 true
                             }
-                        }
-                        EventOption::Quit(_) => break 'events,
-                        _ => false,
-                    };
-
-                    if redraw {
-                        let mut now_selected = -1;
-
-                        if let Some(start) = bar.start.as_ref() {
-                            let mut x = 0;
-                            let y = 0;
-                            let mut i = 0;
-
-                            {
-                                if mouse_y >= y
-                                    && mouse_x >= x
-                                    && mouse_x < x + start.width() as i32
-                                {
-                                    now_selected = i;
-                                }
-                                x += start.width() as i32;
-                                i += 1;
+                            EventOption::Button(button_event) => {
+                                display.mouse_left = button_event.left;
+                                display.mouse_right = button_event.right;
+                                true
                             }
-
-                            for package in bar.packages.iter_mut() {
-                                let image = package.icon.image();
-                                if mouse_y >= y
-                                    && mouse_x >= x
-                                    && mouse_x < x + image.width() as i32
-                                {
-                                    now_selected = i;
+                            EventOption::Screen(_) => {
+                                // Displays may have been added, removed or resized
+                                let (kept, closed) = bar.update_displays();
+                                for display in closed {
+                                    info!("closing bar at {:?}", display.screen);
+                                    unsubscribe_window(&display.window);
                                 }
-                                x += image.width() as i32;
-                                i += 1;
-                            }
-                        }
-
-                        if now_selected != bar.selected {
-                            bar.selected = now_selected;
-                            let sw_y = bar.height as i32;
-                            bar.selected_window.set_pos(0, sw_y);
-                            bar.draw();
-                        }
-
-                        if mouse_left && !last_mouse_left {
-                            let mut i = 0;
-
-                            if i == bar.selected {
-                                let mut category_opt = None;
-                                while let Some(exec) = bar.start_window(category_opt.as_ref()) {
-                                    if exec.starts_with("category=") {
-                                        let category = &exec[9..];
-                                        category_opt = Some(category.to_string());
-                                    } else if exec == "exit" {
-                                        if category_opt.is_some() {
-                                            category_opt = None;
-                                        } else {
-                                            break 'events;
-                                        }
-                                    } else {
-                                        bar.spawn(exec);
-                                        break;
-                                    }
+                                for display in bar.displays[kept..].iter() {
+                                    subscribe_window(&display.window);
+                                }
+                                bar.draw_all();
+                                if display_i >= kept {
+                                    break;
                                 }
+                                false
                             }
-                            i += 1;
-
-                            for package_i in 0..bar.packages.len() {
-                                if i == bar.selected {
-                                    let exec = bar.packages[package_i].exec.clone();
-                                    bar.spawn(exec);
+                            EventOption::Hover(hover_event) => {
+                                if hover_event.entered {
+                                    display.set_hidden(&bar.placement, false);
+                                    false
+                                } else {
+                                    display.mouse_x = -1;
+                                    display.mouse_y = -1;
+                                    display.set_hidden(&bar.placement, true);
+                                    true
                                 }
-                                i += 1;
+                            }
+                            EventOption::Quit(_) => break 'events,
+                            _ => false,
+                        };
+
+                        if redraw {
+                            if let Some(end) = bar.pointer_event(display_i) {
+                                session_end = end;
+                                break 'events;
                             }
                         }
-
-                        last_mouse_left = mouse_left;
                     }
+                    display_i += 1;
+                }
+
+                if !sliding
+                    && bar
+                        .displays
+                        .iter()
+                        .any(|display| display.slide_opt.is_some())
+                {
+                    sliding = true;
+                    set_timer(&mut slide_file, Duration::ZERO)?;
                 }
             }
         }
     }
 
-    debug!("Launcher exiting, killing {} children", bar.children.len());
-    for (exec, child) in bar.children.iter_mut() {
-        let pid = child.id();
-        match child.kill() {
-            Ok(()) => debug!("Successfully killed child: {}", pid),
-            Err(err) => error!("failed to kill {} ({}): {}", exec, pid, err),
-        }
-        match child.wait() {
-            Ok(status) => info!("{} ({}) exited with {}", exec, pid, status),
-            Err(err) => error!("failed to wait for {} ({}): {}", exec, pid, err),
-        }
-    }
+    let mut children = mem::take(&mut bar.children);
+    children.extend(bar.services.take_children());
+    debug!("Launcher exiting, ending {} children", children.len());
+    session::terminate(children, session::grace_period());
 
     // kill any descendents of one of the children killed above that are still running
     debug!("Launcher exiting, reaping all zombie processes");
@@ -833,14 +2272,70 @@ fn bar_main(width: u32, height: u32) -> io::Result<()> {
         }
     }
 
+    if let Some(mut command) = session_end.command() {
+        info!("running {:?}", command);
+        if let Err(err) = command.spawn() {
+            error!("failed to {}: {}", session_end.name(), err);
+        }
+    }
+
     Ok(())
 }
 
+/// Create the menu entry to end the session in a way, with an exec string
+fn session_package(end: SessionEnd, exec: String) -> Package {
+    let mut package = Package::new();
+    package.name = translate(end.label());
+    let icon = format!("{}/icons/actions/{}.png", UI_PATH, end.icon());
+    package.icon.source = IconSource::Path(icon.clone().into());
+    package.icon_small.source = IconSource::Path(icon.into());
+    package.exec = exec;
+    package
+}
+
+/// Add a file to the list of files to launch with a package
+fn add_launch(launches: &mut Vec<(Package, Vec<String>)>, package: &Package, path: &str) {
+    match launches.iter_mut().find(|(x, _)| x.id == package.id) {
+        Some((_, paths)) => paths.push(path.to_string()),
+        None => launches.push((package.clone(), vec![path.to_string()])),
+    }
+}
+
+/// The label of the chooser row that remembers the chosen package as the default
+fn always_label(always: bool) -> String {
+    format!(
+        "[{}] {}",
+        if always { "x" } else { " " },
+        translate("Always use this application")
+    )
+}
+
 fn chooser_main(paths: env::Args) {
+    let all_packages = get_packages();
+    let mime_apps = MimeApps::new();
+
+    // Files are grouped by the package chosen for them, so that packages that take a list of
+    // files are launched once with all of them
+    let mut launches = Vec::new();
     for ref path in paths.skip(1) {
-        let mut packages = get_packages();
+        let mime_type = mime::mime_type(path);
+        debug!("{} has MIME type {}", path, mime_type);
+
+        // The default package is launched without asking
+        if let Some(package) = mime_apps
+            .defaults(&mime_type)
+            .iter()
+            .find_map(|id| all_packages.iter().find(|package| &package.id == id))
+        {
+            add_launch(&mut launches, package, path);
+            continue;
+        }
 
+        let mut packages = all_packages.clone();
         packages.retain(|package| -> bool {
+            if mime_apps.is_removed(&mime_type, &package.id) {
+                return false;
+            }
             for accept in package.accepts.iter() {
                 if (accept.starts_with('*') && path.ends_with(&accept[1..]))
                     || (accept.ends_with('*') && path.starts_with(&accept[..accept.len() - 1]))
@@ -848,10 +2343,24 @@ fn chooser_main(paths: env::Args) {
                     return true;
                 }
             }
-            false
+            package
+                .mime_types
+                .iter()
+                .any(|accepted| mime::mime_matches(accepted, &mime_type))
+                || mime_apps.added(&mime_type).contains(&package.id)
         });
 
         if packages.len() > 1 {
+            // The last row toggles saving the choice as the default
+            let always_i = packages.len();
+            let mut always = false;
+            packages.push({
+                let mut package = Package::new();
+                package.name = always_label(always);
+                package.exec = "always".to_string();
+                package
+            });
+
             let mut window = Window::new(
                 -1,
                 -1,
@@ -870,6 +2379,7 @@ fn chooser_main(paths: env::Args) {
             draw_chooser(&mut window, &font, &mut packages, selected);
             'choosing: loop {
                 for event in window.events() {
+                    let mut chosen_opt = None;
                     let redraw = match event.to_option() {
                         EventOption::Mouse(mouse_event) => {
                             mouse_y = mouse_event.y;
@@ -879,6 +2389,30 @@ fn chooser_main(paths: env::Args) {
                             mouse_left = button_event.left;
                             true
                         }
+                        EventOption::Key(key_event) if key_event.pressed => {
+                            match key_event.scancode {
+                                K_ESC => break 'choosing,
+                                K_ENTER => {
+                                    chosen_opt = usize::try_from(selected).ok();
+                                }
+                                scancode => {
+                                    if let Some(now_selected) =
+                                        navigate(selected, &packages, scancode)
+                                    {
+                                        if now_selected != selected {
+                                            selected = now_selected;
+                                            draw_chooser(
+                                                &mut window,
+                                                &font,
+                                                &mut packages,
+                                                selected,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            false
+                        }
                         EventOption::Quit(_) => break 'choosing,
                         _ => false,
                     };
@@ -888,7 +2422,7 @@ fn chooser_main(paths: env::Args) {
 
                         let mut y = 0;
                         for (i, _package) in packages.iter().enumerate() {
-                            if mouse_y >= y && mouse_y < y + icon_size() {
+                            if mouse_y >= y && mouse_y < y + icon_small_size() {
                                 now_selected = i as i32;
                             }
                             y += icon_small_size();
@@ -901,10 +2435,10 @@ fn chooser_main(paths: env::Args) {
 
                         if !mouse_left && last_mouse_left {
                             let mut y = 0;
-                            for package in packages.iter() {
+                            for i in 0..packages.len() {
                                 if mouse_y >= y && mouse_y < y + icon_small_size() {
-                                    spawn_exec(&package.exec, Some(&path));
-                                    break 'choosing;
+                                    chosen_opt = Some(i);
+                                    break;
                                 }
                                 y += icon_small_size();
                             }
@@ -912,14 +2446,41 @@ fn chooser_main(paths: env::Args) {
 
                         last_mouse_left = mouse_left;
                     }
+
+                    match chosen_opt {
+                        Some(i) if i == always_i => {
+                            always = !always;
+                            packages[always_i].name = always_label(always);
+                            draw_chooser(&mut window, &font, &mut packages, selected);
+                        }
+                        Some(i) if i < always_i => {
+                            let package = &packages[i];
+                            if always {
+                                if let Err(err) = mimeapps::set_default(&mime_type, &package.id) {
+                                    error!(
+                                        "failed to set default for {} to {}: {}",
+                                        mime_type, package.id, err
+                                    );
+                                }
+                            }
+                            add_launch(&mut launches, package, path);
+                            break 'choosing;
+                        }
+                        _ => (),
+                    }
                 }
             }
         } else if let Some(package) = packages.get(0) {
-            spawn_exec(&package.exec, Some(&path));
+            add_launch(&mut launches, package, path);
         } else {
             error!("no application found for '{}'", path);
         }
     }
+
+    for (package, paths) in launches {
+        let paths: Vec<&str> = paths.iter().map(|x| x.as_str()).collect();
+        spawn_exec(&package, &paths);
+    }
 }
 
 fn start_logging() {
@@ -940,13 +2501,14 @@ fn start_logging() {
 fn main() -> Result<(), String> {
     start_logging();
 
-    let (width, height) = orbclient::get_display_size()?;
+    let (_width, height) = orbclient::get_display_size()?;
     SCALE.store((height as isize / 1600) + 1, Ordering::Relaxed);
+    icon_cache::use_disk_cache();
     let paths = env::args();
     if paths.len() > 1 {
         chooser_main(paths);
     } else {
-        bar_main(width, height).map_err(|e| e.to_string())?;
+        bar_main().map_err(|e| e.to_string())?;
     }
 
     Ok(())
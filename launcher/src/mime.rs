@@ -0,0 +1,212 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Magic bytes at the start of a file, and the MIME type they identify
+const MAGIC: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"\x00\x00\x01\x00", "image/vnd.microsoft.icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\xfd7zXZ\x00", "application/x-xz"),
+    (b"(\xb5/\xfd", "application/zstd"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1aE\xdf\xa3", "video/x-matroska"),
+    (b"<svg", "image/svg+xml"),
+    (b"<!DOCTYPE html", "text/html"),
+    (b"<!doctype html", "text/html"),
+    (b"<html", "text/html"),
+];
+
+/// Magic bytes of generic formats, or short enough to start other files by chance, so they are
+/// only used when the extension is unknown
+const WEAK_MAGIC: &[(&[u8], &str)] = &[
+    (b"#!", "application/x-shellscript"),
+    (b"ID3", "audio/mpeg"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"<?xml", "application/xml"),
+];
+
+/// Guess a MIME type from the first bytes of a file
+pub fn magic_mime_type(data: &[u8]) -> Option<&'static str> {
+    // RIFF and ISO media containers have their type after a size field
+    if data.len() >= 12 && &data[..4] == b"RIFF" {
+        match &data[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/x-wav"),
+            b"AVI " => return Some("video/x-msvideo"),
+            _ => (),
+        }
+    }
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return Some("video/mp4");
+    }
+    if data.len() >= 262 && &data[257..262] == b"ustar" {
+        return Some("application/x-tar");
+    }
+    MAGIC
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, mime_type)| *mime_type)
+}
+
+/// Guess a MIME type from the first bytes of a file and its length, for magic bytes that are
+/// less specific than an extension
+pub fn weak_magic_mime_type(data: &[u8], len: u64) -> Option<&'static str> {
+    // A BMP header repeats the file size, followed by four reserved zero bytes
+    if data.len() >= 10 && data.starts_with(b"BM") {
+        let size = u32::from_le_bytes([data[2], data[3], data[4], data[5]]);
+        if u64::from(size) == len && data[6..10] == [0; 4] {
+            return Some("image/bmp");
+        }
+    }
+    WEAK_MAGIC
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, mime_type)| *mime_type)
+}
+
+/// Guess the MIME type of a file or URL
+///
+/// URLs get an `x-scheme-handler` type. Files are identified by their magic bytes, then by their
+/// extension, then by weak magic bytes, and then as text or as an unknown binary.
+pub fn mime_type(path: &str) -> String {
+    if let Some((scheme, _)) = path.split_once("://") {
+        if scheme != "file" && !scheme.is_empty() && scheme.chars().all(|c| c.is_alphanumeric()) {
+            return format!("x-scheme-handler/{}", scheme.to_ascii_lowercase());
+        }
+    }
+    let path = path.strip_prefix("file://").unwrap_or(path);
+
+    if Path::new(path).is_dir() {
+        return "inode/directory".to_string();
+    }
+
+    let mut data = Vec::new();
+    let mut len = 0;
+    if let Ok(file) = File::open(path) {
+        len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let _ = file.take(512).read_to_end(&mut data);
+    }
+
+    if let Some(mime_type) = magic_mime_type(&data) {
+        return mime_type.to_string();
+    }
+
+    if let Some(mime_type) = mime_guess::from_path(path).first_raw() {
+        return mime_type.to_string();
+    }
+
+    if let Some(mime_type) = weak_magic_mime_type(&data, len) {
+        return mime_type.to_string();
+    }
+
+    if std::str::from_utf8(&data).is_ok() {
+        "text/plain".to_string()
+    } else {
+        "application/octet-stream".to_string()
+    }
+}
+
+/// Check if a MIME type from a desktop entry's `MimeType` list accepts a MIME type
+pub fn mime_matches(accepted: &str, mime_type: &str) -> bool {
+    if accepted == mime_type {
+        return true;
+    }
+    if let Some(top_level) = accepted.strip_suffix("/*") {
+        return mime_type.split('/').next() == Some(top_level);
+    }
+    // Every text type can be opened as plain text
+    accepted == "text/plain" && mime_type.starts_with("text/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn magic() {
+        assert_eq!(magic_mime_type(b"\x89PNG\r\n\x1a\n\0\0"), Some("image/png"));
+        assert_eq!(magic_mime_type(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(magic_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(magic_mime_type(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(magic_mime_type(b"hello world"), None);
+        assert_eq!(magic_mime_type(b""), None);
+    }
+
+    #[test]
+    fn weak_magic() {
+        let mut bmp = b"BM\x20\0\0\0\0\0\0\0".to_vec();
+        bmp.resize(32, 0);
+        assert_eq!(weak_magic_mime_type(&bmp, 32), Some("image/bmp"));
+        assert_eq!(weak_magic_mime_type(&bmp, 33), None);
+        assert_eq!(weak_magic_mime_type(b"BMW service notes\n", 18), None);
+        assert_eq!(
+            weak_magic_mime_type(b"#!/bin/sh\n", 10),
+            Some("application/x-shellscript")
+        );
+        assert_eq!(magic_mime_type(b"#!/bin/sh\n"), None);
+    }
+
+    #[test]
+    fn files() {
+        let dir = env::temp_dir().join(format!("launcher-mime-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = |name: &str, data: &[u8]| {
+            let path = dir.join(name);
+            fs::write(&path, data).unwrap();
+            mime_type(path.to_str().unwrap())
+        };
+
+        // Text starting like a BMP header is not an image
+        assert_eq!(file("notes.txt", b"BMW service notes\n"), "text/plain");
+        assert_eq!(file("notes", b"BMW service notes\n"), "text/plain");
+        // Extensions are more specific than a shebang
+        assert_eq!(
+            file("script.pl", b"#!/usr/bin/perl\n"),
+            "application/x-perl"
+        );
+        assert_eq!(file("script", b"#!/bin/sh\n"), "application/x-shellscript");
+        assert_eq!(file("picture", b"\x89PNG\r\n\x1a\n\0\0"), "image/png");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn urls() {
+        assert_eq!(mime_type("https://redox-os.org"), "x-scheme-handler/https");
+        assert_eq!(mime_type("ftp://example.com/file"), "x-scheme-handler/ftp");
+    }
+
+    #[test]
+    fn extensions() {
+        // Files that do not exist are identified only by their extension
+        assert_eq!(mime_type("/nonexistent/document.pdf"), "application/pdf");
+        assert_eq!(mime_type("/nonexistent/picture.PNG"), "image/png");
+        assert_eq!(mime_type("file:///nonexistent/page.html"), "text/html");
+    }
+
+    #[test]
+    fn directory() {
+        assert_eq!(mime_type("/"), "inode/directory");
+    }
+
+    #[test]
+    fn matches() {
+        assert!(mime_matches("application/pdf", "application/pdf"));
+        assert!(!mime_matches("application/pdf", "image/png"));
+        assert!(mime_matches("image/*", "image/png"));
+        assert!(!mime_matches("image/*", "video/mp4"));
+        assert!(mime_matches("text/plain", "text/x-rust"));
+        assert!(!mime_matches("text/x-rust", "text/plain"));
+    }
+}
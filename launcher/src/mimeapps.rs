@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::package::current_desktops;
+
+const DEFAULT_APPLICATIONS: &str = "Default Applications";
+const ADDED_ASSOCIATIONS: &str = "Added Associations";
+const REMOVED_ASSOCIATIONS: &str = "Removed Associations";
+
+/// Parse the groups of a `mimeapps.list` file into lists of desktop IDs by MIME type
+fn parse(data: &str) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+    let mut groups = BTreeMap::<String, BTreeMap<String, Vec<String>>>::new();
+    let mut group_opt = None;
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            group_opt = Some(group.to_string());
+            continue;
+        }
+        let (Some(group), Some((key, value))) = (&group_opt, line.split_once('=')) else {
+            continue;
+        };
+        let ids = groups
+            .entry(group.clone())
+            .or_default()
+            .entry(key.trim().to_string())
+            .or_default();
+        for id in value.split_terminator(';') {
+            let id = id.trim();
+            if !id.is_empty() {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    groups
+}
+
+/// Find the `mimeapps.list` files, most important first
+fn mimeapps_paths() -> Vec<PathBuf> {
+    let Ok(xdg_dirs) = xdg::BaseDirectories::new() else {
+        return Vec::new();
+    };
+
+    let mut dirs = vec![xdg_dirs.get_config_home()];
+    dirs.extend(xdg_dirs.get_config_dirs());
+    dirs.push(xdg_dirs.get_data_home().join("applications"));
+    dirs.extend(
+        xdg_dirs
+            .get_data_dirs()
+            .into_iter()
+            .map(|dir| dir.join("applications")),
+    );
+
+    let desktops = current_desktops();
+    let mut paths = Vec::new();
+    for dir in dirs {
+        for desktop in desktops.iter() {
+            paths.push(dir.join(format!("{}-mimeapps.list", desktop.to_lowercase())));
+        }
+        paths.push(dir.join("mimeapps.list"));
+    }
+    paths
+}
+
+/// Associations between MIME types and applications, from the `mimeapps.list` files
+///
+/// See https://specifications.freedesktop.org/mime-apps-spec/latest/
+#[derive(Default)]
+pub struct MimeApps {
+    defaults: BTreeMap<String, Vec<String>>,
+    added: BTreeMap<String, Vec<String>>,
+    removed: BTreeMap<String, Vec<String>>,
+}
+
+impl MimeApps {
+    pub fn new() -> Self {
+        let mut mime_apps = MimeApps::default();
+        for path in mimeapps_paths() {
+            if let Ok(data) = fs::read_to_string(&path) {
+                mime_apps.add(&data);
+            }
+        }
+        mime_apps
+    }
+
+    /// Add the associations of a file, less important than the ones already added
+    pub fn add(&mut self, data: &str) {
+        let mut groups = parse(data);
+        for (group, map) in [
+            (DEFAULT_APPLICATIONS, &mut self.defaults),
+            (ADDED_ASSOCIATIONS, &mut self.added),
+            (REMOVED_ASSOCIATIONS, &mut self.removed),
+        ] {
+            for (mime_type, ids) in groups.remove(group).unwrap_or_default() {
+                map.entry(mime_type).or_default().extend(ids);
+            }
+        }
+    }
+
+    /// The preferred applications for a MIME type, most preferred first
+    pub fn defaults(&self, mime_type: &str) -> &[String] {
+        self.defaults.get(mime_type).map_or(&[], |x| x.as_slice())
+    }
+
+    /// The applications associated with a MIME type, in addition to their own `MimeType` key
+    pub fn added(&self, mime_type: &str) -> &[String] {
+        self.added.get(mime_type).map_or(&[], |x| x.as_slice())
+    }
+
+    /// Check if an association between a MIME type and an application was removed
+    pub fn is_removed(&self, mime_type: &str, id: &str) -> bool {
+        self.removed
+            .get(mime_type)
+            .is_some_and(|ids| ids.iter().any(|x| x == id))
+    }
+}
+
+/// Set the default application for a MIME type in the per-user `mimeapps.list`
+pub fn set_default(mime_type: &str, id: &str) -> io::Result<()> {
+    let xdg_dirs = xdg::BaseDirectories::new()?;
+    let path = xdg_dirs.place_config_file("mimeapps.list")?;
+    let old_data = fs::read_to_string(&path).unwrap_or_default();
+    fs::write(&path, with_default(&old_data, mime_type, id))
+}
+
+/// Replace or add a default application in the contents of a `mimeapps.list` file
+fn with_default(old_data: &str, mime_type: &str, id: &str) -> String {
+    let new_line = format!("{}={};", mime_type, id);
+    let mut data = String::new();
+    let mut in_defaults = false;
+    let mut found = false;
+    for line in old_data.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_defaults && !found {
+                data.push_str(&new_line);
+                data.push('\n');
+                found = true;
+            }
+            in_defaults = trimmed == format!("[{}]", DEFAULT_APPLICATIONS);
+        } else if in_defaults {
+            if let Some((key, _)) = trimmed.split_once('=') {
+                if key.trim() == mime_type {
+                    if !found {
+                        data.push_str(&new_line);
+                        data.push('\n');
+                        found = true;
+                    }
+                    continue;
+                }
+            }
+        }
+        data.push_str(line);
+        data.push('\n');
+    }
+    if !found {
+        if !in_defaults {
+            if !data.is_empty() && !data.ends_with("\n\n") {
+                data.push('\n');
+            }
+            data.push_str(&format!("[{}]\n", DEFAULT_APPLICATIONS));
+        }
+        data.push_str(&new_line);
+        data.push('\n');
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "[Default Applications]
+text/plain=editor.desktop;
+image/png=viewer.desktop;paint.desktop;
+
+[Added Associations]
+text/plain=notes.desktop;
+
+[Removed Associations]
+image/png=browser.desktop;
+";
+
+    const SYSTEM: &str = "[Default Applications]
+text/plain=other-editor.desktop
+application/pdf=reader.desktop
+";
+
+    #[test]
+    fn priority() {
+        let mut mime_apps = MimeApps::default();
+        mime_apps.add(USER);
+        mime_apps.add(SYSTEM);
+        assert_eq!(
+            mime_apps.defaults("text/plain"),
+            ["editor.desktop", "other-editor.desktop"]
+        );
+        assert_eq!(
+            mime_apps.defaults("image/png"),
+            ["viewer.desktop", "paint.desktop"]
+        );
+        assert_eq!(mime_apps.defaults("application/pdf"), ["reader.desktop"]);
+        assert!(mime_apps.defaults("video/mp4").is_empty());
+        assert_eq!(mime_apps.added("text/plain"), ["notes.desktop"]);
+        assert!(mime_apps.is_removed("image/png", "browser.desktop"));
+        assert!(!mime_apps.is_removed("image/png", "viewer.desktop"));
+    }
+
+    #[test]
+    fn replace_default() {
+        let data = with_default(USER, "image/png", "paint.desktop");
+        let mut mime_apps = MimeApps::default();
+        mime_apps.add(&data);
+        assert_eq!(mime_apps.defaults("image/png"), ["paint.desktop"]);
+        assert_eq!(mime_apps.defaults("text/plain"), ["editor.desktop"]);
+        assert_eq!(mime_apps.added("text/plain"), ["notes.desktop"]);
+    }
+
+    #[test]
+    fn add_default() {
+        let data = with_default(USER, "video/mp4", "player.desktop");
+        let mut mime_apps = MimeApps::default();
+        mime_apps.add(&data);
+        assert_eq!(mime_apps.defaults("video/mp4"), ["player.desktop"]);
+        assert_eq!(mime_apps.defaults("text/plain"), ["editor.desktop"]);
+    }
+
+    #[test]
+    fn new_file() {
+        assert_eq!(
+            with_default("", "video/mp4", "player.desktop"),
+            "[Default Applications]\nvideo/mp4=player.desktop;\n"
+        );
+        assert_eq!(
+            with_default("[Added Associations]\ntext/plain=notes.desktop;\n", "video/mp4", "player.desktop"),
+            "[Added Associations]\ntext/plain=notes.desktop;\n\n[Default Applications]\nvideo/mp4=player.desktop;\n"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::io::{self, ErrorKind, Read};
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use orbutils_shared::notification::{socket_path, Notification};
+
+/// How long the launcher waits for a client to send a whole notification
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The largest notification the launcher reads, larger ones are dropped
+const MAX_NOTIFICATION_SIZE: usize = 64 * 1024;
+
+/// A connection that has not sent a whole notification yet
+struct Client {
+    stream: UnixStream,
+    data: Vec<u8>,
+    accepted: Instant,
+}
+
+impl Client {
+    /// Read what the client has sent without blocking, returns the data once it closes the
+    /// connection, or None while more may follow
+    fn read(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut buf = [0; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Ok(Some(mem::take(&mut self.data))),
+                Ok(count) => {
+                    if self.data.len() + count > MAX_NOTIFICATION_SIZE {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("larger than {} bytes", MAX_NOTIFICATION_SIZE),
+                        ));
+                    }
+                    self.data.extend_from_slice(&buf[..count]);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    if self.accepted.elapsed() > READ_TIMEOUT {
+                        return Err(io::Error::new(ErrorKind::TimedOut, "client too slow"));
+                    }
+                    return Ok(None);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Receives notifications on the launcher socket, one per connection
+pub struct NotificationServer {
+    listener: UnixListener,
+    path: PathBuf,
+    /// The connections still sending, which are read again on the next call to `accept`
+    clients: Vec<Client>,
+}
+
+impl NotificationServer {
+    pub fn bind() -> io::Result<Self> {
+        Self::bind_at(socket_path())
+    }
+
+    fn bind_at(path: PathBuf) -> io::Result<Self> {
+        // A socket left behind by a launcher that did not exit cleanly would block binding, but
+        // one that is still accepted on belongs to another launcher
+        match UnixStream::connect(&path) {
+            Ok(_) => {
+                return Err(io::Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("{} is in use by another launcher", path.display()),
+                ))
+            }
+            Err(err) if err.kind() == ErrorKind::ConnectionRefused => {
+                fs::remove_file(&path)?;
+                log::debug!("removed stale socket {}", path.display());
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        Ok(NotificationServer {
+            listener,
+            path,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Accept every pending connection and read the notifications that have been sent in whole
+    ///
+    /// Reads never block, so this is also called on the timer to finish slow clients and drop
+    /// those that take longer than `READ_TIMEOUT`.
+    pub fn accept(&mut self) -> Vec<Notification> {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    log::error!("failed to accept notification client: {}", err);
+                    break;
+                }
+            };
+            if let Err(err) = stream.set_nonblocking(true) {
+                log::error!("failed to accept notification client: {}", err);
+                continue;
+            }
+            self.clients.push(Client {
+                stream,
+                data: Vec::new(),
+                accepted: Instant::now(),
+            });
+        }
+
+        let mut notifications = Vec::new();
+        self.clients.retain_mut(|client| {
+            let data = match client.read() {
+                Ok(Some(data)) => data,
+                Ok(None) => return true,
+                Err(err) => {
+                    log::error!("failed to read notification: {}", err);
+                    return false;
+                }
+            };
+            match Notification::decode(&String::from_utf8_lossy(&data)) {
+                Ok(notification) => notifications.push(notification),
+                Err(err) => log::error!("invalid notification: {}", err),
+            }
+            false
+        });
+        notifications
+    }
+}
+
+impl AsRawFd for NotificationServer {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+impl Drop for NotificationServer {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!("failed to remove socket {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::Write;
+
+    #[test]
+    fn server() {
+        let dir = env::temp_dir().join(format!("launcher-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("launcher-notifications");
+
+        let mut server = NotificationServer::bind_at(path.clone()).unwrap();
+        assert!(server.accept().is_empty());
+        let notification = Notification {
+            summary: "Hello".to_string(),
+            ..Notification::default()
+        };
+        notification.send_to(&path).unwrap();
+        assert_eq!(server.accept(), vec![notification.clone()]);
+
+        // The socket of a running server is kept
+        assert!(NotificationServer::bind_at(path.clone()).is_err());
+        assert!(path.exists());
+
+        // Notifications over the size limit are dropped
+        let large = Notification {
+            body: "x".repeat(MAX_NOTIFICATION_SIZE),
+            ..notification.clone()
+        };
+        large.send_to(&path).unwrap();
+        assert!(server.accept().is_empty());
+        assert!(server.clients.is_empty());
+
+        // A client that has not finished sending is read again later
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(b"summary=").unwrap();
+        assert!(server.accept().is_empty());
+        stream.write_all(b"Hello\n").unwrap();
+        drop(stream);
+        assert_eq!(server.accept(), vec![notification]);
+
+        drop(server);
+        assert!(!path.exists());
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+//! Send a desktop notification to the launcher, like `notify-send`
+
+use std::env;
+use std::process;
+use std::time::Duration;
+
+use orbutils_shared::notification::Notification;
+
+fn usage() -> ! {
+    eprintln!("usage: notify [-a APP_NAME] [-i ICON] [-t TIMEOUT_MS] SUMMARY [BODY]");
+    process::exit(1);
+}
+
+fn main() {
+    let mut notification = Notification::default();
+    let mut texts = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-a" | "--app-name" => notification.app_name = args.next().unwrap_or_else(|| usage()),
+            "-i" | "--icon" => notification.icon = args.next().unwrap_or_else(|| usage()),
+            "-t" | "--expire-time" => {
+                let timeout = args.next().unwrap_or_else(|| usage());
+                match timeout.parse::<u64>() {
+                    Ok(ms) => notification.timeout = Some(Duration::from_millis(ms)),
+                    Err(err) => {
+                        eprintln!("notify: invalid timeout {:?}: {}", timeout, err);
+                        process::exit(1);
+                    }
+                }
+            }
+            "-h" | "--help" => usage(),
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                eprintln!("notify: unknown option {}", arg);
+                usage();
+            }
+            _ => texts.push(arg),
+        }
+    }
+
+    let mut texts = texts.into_iter();
+    notification.summary = texts.next().unwrap_or_else(|| usage());
+    notification.body = texts.next().unwrap_or_default();
+    if texts.next().is_some() {
+        usage();
+    }
+
+    if let Err(err) = notification.send() {
+        eprintln!("notify: failed to send notification: {}", err);
+        process::exit(1);
+    }
+}
@@ -1,15 +1,19 @@
 use std::collections::BTreeSet;
-use std::ffi::OsStr;
+use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::atomic::Ordering;
 
+use freedesktop_entry_parser::Entry;
 use orbclient::image::Image;
 
-use super::{load_icon, load_icon_small, load_icon_svg};
+use crate::exec::executable_exists;
+use crate::locale::localized_attr;
+use crate::{icon_cache, icon_theme};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IconSource {
     None,
     Name(String),
@@ -22,12 +26,7 @@ impl IconSource {
             IconSource::Name(name) => {
                 let size = if small { 32 } else { 48 };
                 let scale = crate::SCALE.load(Ordering::Relaxed) as u16;
-                if let Some(path) = freedesktop_icons::lookup(name)
-                    .with_size(size)
-                    .with_scale(scale)
-                    .with_theme("Cosmic")
-                    .find()
-                {
+                if let Some(path) = icon_theme::lookup(name, size, scale) {
                     *self = IconSource::Path(path)
                 } else {
                     log::warn!("failed to find icon {name} with size {size} and scale {scale}");
@@ -46,7 +45,7 @@ impl IconSource {
 pub struct Icon {
     pub source: IconSource,
     small: bool,
-    image_opt: Option<Image>,
+    image_opt: Option<Rc<Image>>,
 }
 
 impl Icon {
@@ -58,33 +57,44 @@ impl Icon {
         }
     }
 
+    /// Get the image, which is shared with every icon with the same source
     pub fn image(&mut self) -> &Image {
         if self.image_opt.is_none() {
-            log::debug!("loading {:?}", self.source);
-            self.image_opt = if let Some(path) = self.source.lookup(self.small) {
-                if path.extension() == Some(OsStr::new("png")) {
-                    if self.small {
-                        load_icon_small(&path)
-                    } else {
-                        load_icon(&path)
-                    }
-                } else if path.extension() == Some(OsStr::new("svg")) {
-                    load_icon_svg(&path, self.small)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            if self.image_opt.is_none() {
-                println!("failed to load icon {:?}", self.source);
-                self.image_opt = Some(Image::new(0, 0));
-            }
+            self.image_opt = Some(icon_cache::icon(&self.source, self.small));
         }
         self.image_opt.as_ref().unwrap()
     }
 }
 
+/// The desktop names matched against `OnlyShowIn` and `NotShowIn`, from `XDG_CURRENT_DESKTOP`
+pub fn current_desktops() -> Vec<String> {
+    match env::var("XDG_CURRENT_DESKTOP") {
+        Ok(desktops) if !desktops.is_empty() => desktops.split(':').map(String::from).collect(),
+        _ => vec!["Redox".to_string()],
+    }
+}
+
+/// Check the `OnlyShowIn` and `NotShowIn` lists of a desktop entry against the current desktops
+pub fn shown_in(only_show_in: Option<&str>, not_show_in: Option<&str>) -> bool {
+    let desktops = current_desktops();
+    let listed = |list: &str| {
+        list.split_terminator(';')
+            .any(|x| desktops.iter().any(|desktop| desktop == x))
+    };
+    only_show_in.is_none_or(listed) && !not_show_in.is_some_and(listed)
+}
+
+/// An extra way to launch a package, from a `[Desktop Action]` group
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesktopAction {
+    /// The ID of the action
+    pub id: String,
+    /// The name of the action
+    pub name: String,
+    /// The exec string for the action
+    pub exec: String,
+}
+
 /// A package (_REDOX content serialized)
 #[derive(Clone)]
 pub struct Package {
@@ -102,10 +112,30 @@ pub struct Package {
     pub icon_small: Icon,
     /// The accepted extensions
     pub accepts: Vec<String>,
+    /// The accepted MIME types
+    pub mime_types: Vec<String>,
+    /// The generic name of the package, like "Web Browser"
+    pub generic_name: Option<String>,
+    /// A short comment describing the package
+    pub comment: Option<String>,
     /// The author(s) of the package
     pub authors: Vec<String>,
     /// The description of the package
     pub descriptions: Vec<String>,
+    /// Extra search terms for the package
+    pub keywords: Vec<String>,
+    /// The icon name or path, for the `%i` field code
+    pub icon_name: Option<String>,
+    /// The file the package was loaded from, for the `%k` field code
+    pub desktop_path: Option<PathBuf>,
+    /// The working directory to launch the package in
+    pub working_dir: Option<PathBuf>,
+    /// True if the package is launched in a terminal
+    pub terminal: bool,
+    /// True if the package should not be shown in the bar or menus
+    pub no_display: bool,
+    /// Extra ways to launch the package, from desktop entry actions
+    pub actions: Vec<DesktopAction>,
 }
 
 impl Package {
@@ -118,8 +148,18 @@ impl Package {
             icon: Icon::empty(false),
             icon_small: Icon::empty(true),
             accepts: Vec::new(),
+            mime_types: Vec::new(),
+            generic_name: None,
+            comment: None,
             authors: Vec::new(),
             descriptions: Vec::new(),
+            keywords: Vec::new(),
+            icon_name: None,
+            desktop_path: None,
+            working_dir: None,
+            terminal: false,
+            no_display: false,
+            actions: Vec::new(),
         }
     }
 
@@ -134,6 +174,8 @@ impl Package {
             }
         }
 
+        package.desktop_path = Some(PathBuf::from(path));
+
         let mut info = String::new();
 
         if let Ok(mut file) = File::open(path) {
@@ -160,6 +202,7 @@ impl Package {
                 }
             } else if line.starts_with("icon=") {
                 let path = Path::new(&line[5..]);
+                package.icon_name = Some(line[5..].to_string());
                 package.icon.source = IconSource::Path(path.into());
                 package.icon_small.source = IconSource::Path(path.into());
             } else if line.starts_with("accept=") {
@@ -178,12 +221,43 @@ impl Package {
 
     pub fn from_desktop_entry(id: String, path: &Path) -> Option<Self> {
         let entry = freedesktop_entry_parser::parse_entry(path).ok()?;
+        let mut package = Self::from_entry(id, &entry)?;
+        package.desktop_path = Some(path.to_path_buf());
+        Some(package)
+    }
+
+    /// Create package from a parsed desktop entry, returns None if it is hidden or not installed
+    pub fn from_entry(id: String, entry: &Entry) -> Option<Self> {
         let mut package = Package::new();
         package.id = id;
         let section = entry.section("Desktop Entry");
-        if let Some(name) = section.attr("Name") {
+        if section.attr("Type").is_some_and(|x| x != "Application") {
+            return None;
+        }
+        if section.attr("Hidden") == Some("true") {
+            return None;
+        }
+        if let Some(try_exec) = section.attr("TryExec") {
+            if !executable_exists(try_exec) {
+                log::debug!("{} not installed, {} not found", package.id, try_exec);
+                return None;
+            }
+        }
+        if section.attr("NoDisplay") == Some("true") {
+            package.no_display = true;
+        }
+        if !shown_in(section.attr("OnlyShowIn"), section.attr("NotShowIn")) {
+            package.no_display = true;
+        }
+        if let Some(name) = localized_attr(&section, "Name") {
             package.name = name.into();
         }
+        if let Some(generic_name) = localized_attr(&section, "GenericName") {
+            package.generic_name = Some(generic_name.into());
+        }
+        if let Some(comment) = localized_attr(&section, "Comment") {
+            package.comment = Some(comment.into());
+        }
         if let Some(categories) = section.attr("Categories") {
             // From https://specifications.freedesktop.org/menu-spec/latest/category-registry.html#main-category-registry
             let main_categories = [
@@ -218,10 +292,223 @@ impl Package {
         if let Some(exec) = section.attr("Exec") {
             package.exec = exec.into();
         }
+        if let Some(mime_types) = section.attr("MimeType") {
+            for mime_type in mime_types.split_terminator(';') {
+                package.mime_types.push(mime_type.into());
+            }
+        }
+        if let Some(keywords) = localized_attr(&section, "Keywords") {
+            for keyword in keywords.split_terminator(';') {
+                package.keywords.push(keyword.into());
+            }
+        }
         if let Some(icon) = section.attr("Icon") {
             package.icon.source = IconSource::Name(icon.into());
             package.icon_small.source = IconSource::Name(icon.into());
+            package.icon_name = Some(icon.into());
+        }
+        if let Some(path) = section.attr("Path") {
+            if !path.is_empty() {
+                package.working_dir = Some(path.into());
+            }
+        }
+        if section.attr("Terminal") == Some("true") {
+            package.terminal = true;
+        }
+        if let Some(actions) = section.attr("Actions") {
+            for action_id in actions.split_terminator(';') {
+                let action_section = entry.section(format!("Desktop Action {}", action_id));
+                let (Some(name), Some(exec)) = (
+                    localized_attr(&action_section, "Name"),
+                    action_section.attr("Exec"),
+                ) else {
+                    log::warn!("{}: invalid desktop action {:?}", package.id, action_id);
+                    continue;
+                };
+                package.actions.push(DesktopAction {
+                    id: action_id.into(),
+                    name: name.into(),
+                    exec: exec.into(),
+                });
+            }
         }
         Some(package)
     }
 }
+
+/// Load the desktop entries in `applications` data directories, in order of priority
+///
+/// Only the first entry with a desktop file ID is used, so an entry in a directory of higher
+/// priority overrides, or hides, the entries with the same ID after it.
+pub fn load_desktop_entries(dirs: &[PathBuf]) -> Vec<Package> {
+    let mut ids = BTreeSet::new();
+    let mut packages = Vec::new();
+    for dir in dirs {
+        let Ok(read_dir) = dir.read_dir() else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = read_dir
+            .filter_map(|entry_res| entry_res.ok())
+            .map(|entry| entry.path())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Some(id) = path
+                .file_name()
+                .and_then(|x| x.to_str())
+                .map(|x| x.to_string())
+            else {
+                continue;
+            };
+            if !id.ends_with(".desktop") || !ids.insert(id.clone()) {
+                continue;
+            }
+            if let Some(package) = Package::from_desktop_entry(id, &path) {
+                packages.push(package);
+            }
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn package(entry: &str) -> Option<Package> {
+        let entry = Entry::parse(entry).unwrap();
+        Package::from_entry("test.desktop".to_string(), &entry)
+    }
+
+    #[test]
+    fn keys() {
+        let package = package(
+            "[Desktop Entry]
+Type=Application
+Name=Test
+Exec=test %F
+Icon=test-icon
+Path=/tmp
+Terminal=true
+MimeType=text/plain;image/png;
+Categories=Utility;Game;
+",
+        )
+        .unwrap();
+        assert_eq!(package.name, "Test");
+        assert_eq!(package.exec, "test %F");
+        assert_eq!(package.icon_name.as_deref(), Some("test-icon"));
+        assert_eq!(package.working_dir, Some(PathBuf::from("/tmp")));
+        assert!(package.terminal);
+        assert_eq!(package.mime_types, vec!["text/plain", "image/png"]);
+        assert!(!package.no_display);
+        assert!(package.categories.contains("Utility"));
+        assert!(package.categories.contains("Games"));
+    }
+
+    #[test]
+    fn actions() {
+        let package = package(
+            "[Desktop Entry]
+Name=Browser
+Exec=browser %u
+Actions=new-window;new-private-window;missing;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=browser --new-window
+
+[Desktop Action new-private-window]
+Name=New Private Window
+Exec=browser --private-window
+",
+        )
+        .unwrap();
+        assert_eq!(
+            package.actions,
+            vec![
+                DesktopAction {
+                    id: "new-window".into(),
+                    name: "New Window".into(),
+                    exec: "browser --new-window".into(),
+                },
+                DesktopAction {
+                    id: "new-private-window".into(),
+                    name: "New Private Window".into(),
+                    exec: "browser --private-window".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden() {
+        assert!(package("[Desktop Entry]\nName=Test\nExec=test\nHidden=true\n").is_none());
+        assert!(package("[Desktop Entry]\nName=Test\nExec=test\nHidden=false\n").is_some());
+        assert!(package("[Desktop Entry]\nType=Link\nName=Test\nURL=test\n").is_none());
+    }
+
+    #[test]
+    fn no_display() {
+        let package = package("[Desktop Entry]\nName=Test\nExec=test\nNoDisplay=true\n").unwrap();
+        assert!(package.no_display);
+    }
+
+    #[test]
+    fn try_exec() {
+        assert!(package("[Desktop Entry]\nName=Test\nExec=sh\nTryExec=/bin/sh\n").is_some());
+        assert!(
+            package("[Desktop Entry]\nName=Test\nExec=test\nTryExec=/nonexistent/test\n").is_none()
+        );
+    }
+
+    #[test]
+    fn show_in() {
+        let desktops = current_desktops();
+        let desktop = &desktops[0];
+        let only = format!("[Desktop Entry]\nName=Test\nExec=test\nOnlyShowIn={desktop};\n");
+        assert!(!package(&only).unwrap().no_display);
+        let only_other = "[Desktop Entry]\nName=Test\nExec=test\nOnlyShowIn=NotADesktop;\n";
+        assert!(package(only_other).unwrap().no_display);
+        let not =
+            format!("[Desktop Entry]\nName=Test\nExec=test\nNotShowIn=NotADesktop;{desktop};\n");
+        assert!(package(&not).unwrap().no_display);
+        let not_other = "[Desktop Entry]\nName=Test\nExec=test\nNotShowIn=NotADesktop;\n";
+        assert!(!package(not_other).unwrap().no_display);
+    }
+
+    #[test]
+    fn overrides() {
+        let dir = env::temp_dir().join(format!("launcher-entries-{}", std::process::id()));
+        let user = dir.join("user");
+        let system = dir.join("system");
+        let entry = |dir: &Path, id: &str, data: &str| {
+            fs::create_dir_all(dir).unwrap();
+            let data = format!("[Desktop Entry]\nType=Application\nExec=test\n{}", data);
+            fs::write(dir.join(id), data).unwrap();
+        };
+        entry(&user, "editor.desktop", "Name=Editor\nHidden=true\n");
+        entry(&user, "viewer.desktop", "Name=My Viewer\n");
+        entry(&system, "editor.desktop", "Name=Editor\n");
+        entry(&system, "viewer.desktop", "Name=Viewer\n");
+        entry(&system, "player.desktop", "Name=Player\n");
+
+        // The user entries hide and replace the system entries with the same ID
+        let packages = load_desktop_entries(&[user.clone(), system.clone()]);
+        let names: Vec<(&str, &str)> = packages
+            .iter()
+            .map(|x| (x.id.as_str(), x.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("viewer.desktop", "My Viewer"),
+                ("player.desktop", "Player")
+            ]
+        );
+        assert_eq!(packages[0].desktop_path, Some(user.join("viewer.desktop")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
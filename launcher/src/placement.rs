@@ -0,0 +1,357 @@
+use crate::config::config_lines;
+
+/// The number of pixels of a hidden bar left on the screen to catch the pointer
+pub const HIDDEN_SIZE: u32 = 2;
+
+/// The screen edge the bar is placed on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top" => Some(Edge::Top),
+            "bottom" => Some(Edge::Bottom),
+            "left" => Some(Edge::Left),
+            "right" => Some(Edge::Right),
+            _ => None,
+        }
+    }
+}
+
+/// A rectangle on the screen, as x, y, width and height
+pub type Rect = (i32, i32, u32, u32);
+
+/// The displays that get a bar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Displays {
+    /// Every display has a bar
+    All,
+    /// Only the display with this index has a bar
+    Primary(usize),
+}
+
+/// Where the bar is placed, from `launcher/bar`
+pub struct Placement {
+    /// The screen edge the bar is placed on
+    pub edge: Edge,
+    /// True if the bar is hidden until the pointer reaches its edge
+    pub auto_hide: bool,
+    /// The displays that get a bar
+    pub displays: Displays,
+}
+
+impl Placement {
+    pub fn new() -> Self {
+        let mut placement = Placement {
+            edge: Edge::Bottom,
+            auto_hide: false,
+            displays: Displays::Primary(0),
+        };
+        for (key, value) in config_lines("bar") {
+            match key.as_str() {
+                "edge" => match Edge::from_name(&value) {
+                    Some(edge) => placement.edge = edge,
+                    None => log::error!("invalid bar edge {:?}", value),
+                },
+                "auto_hide" => placement.auto_hide = value == "true",
+                "displays" => match value.as_str() {
+                    "all" => placement.displays = Displays::All,
+                    _ => match value.parse() {
+                        Ok(index) => placement.displays = Displays::Primary(index),
+                        Err(_) => log::error!("invalid bar displays {:?}", value),
+                    },
+                },
+                _ => log::error!("unknown bar setting {:?}", key),
+            }
+        }
+        placement
+    }
+
+    /// Choose the displays that get a bar, falling back to the first display if the primary
+    /// display is not connected
+    pub fn select_displays(&self, screens: &[Rect]) -> Vec<Rect> {
+        match self.displays {
+            Displays::All => screens.to_vec(),
+            Displays::Primary(index) => screens
+                .get(index)
+                .or(screens.first())
+                .into_iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// True if the bar is on a side edge, with its items laid out top to bottom
+    pub fn vertical(&self) -> bool {
+        matches!(self.edge, Edge::Left | Edge::Right)
+    }
+
+    /// Get the coordinates of a point in the bar as the distance along the bar and across it
+    pub fn along_across(&self, x: i32, y: i32) -> (i32, i32) {
+        if self.vertical() {
+            (y, x)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Get the coordinates of a point in the bar from the distance along the bar and across it
+    pub fn to_xy(&self, along: i32, across: i32) -> (i32, i32) {
+        if self.vertical() {
+            (across, along)
+        } else {
+            (along, across)
+        }
+    }
+
+    /// The rectangle of the bar on a screen, for a bar `thickness` pixels thick
+    pub fn bar_rect(&self, screen: Rect, thickness: u32) -> Rect {
+        let (screen_x, screen_y, screen_w, screen_h) = screen;
+        match self.edge {
+            Edge::Top => (screen_x, screen_y, screen_w, thickness),
+            Edge::Bottom => (
+                screen_x,
+                screen_y + screen_h as i32 - thickness as i32,
+                screen_w,
+                thickness,
+            ),
+            Edge::Left => (screen_x, screen_y, thickness, screen_h),
+            Edge::Right => (
+                screen_x + screen_w as i32 - thickness as i32,
+                screen_y,
+                thickness,
+                screen_h,
+            ),
+        }
+    }
+
+    /// The rectangle of the bar when it is hidden, with only `HIDDEN_SIZE` pixels on the screen
+    pub fn hidden_rect(&self, screen: Rect, thickness: u32) -> Rect {
+        let (x, y, w, h) = self.bar_rect(screen, thickness);
+        let offset = thickness.saturating_sub(HIDDEN_SIZE) as i32;
+        match self.edge {
+            Edge::Top => (x, y - offset, w, h),
+            Edge::Bottom => (x, y + offset, w, h),
+            Edge::Left => (x - offset, y, w, h),
+            Edge::Right => (x + offset, y, w, h),
+        }
+    }
+
+    /// The position of a popup of size `w` by `h` next to the bar, starting `along` pixels along
+    /// the bar, and kept on the screen
+    pub fn popup_pos(
+        &self,
+        bar_rect: Rect,
+        along: i32,
+        w: u32,
+        h: u32,
+        screen: Rect,
+    ) -> (i32, i32) {
+        let (screen_x, screen_y, screen_w, screen_h) = screen;
+        let (bar_x, bar_y, bar_w, bar_h) = bar_rect;
+        let (x, y) = match self.edge {
+            Edge::Top => (bar_x + along, bar_y + bar_h as i32),
+            Edge::Bottom => (bar_x + along, bar_y - h as i32),
+            Edge::Left => (bar_x + bar_w as i32, bar_y + along),
+            Edge::Right => (bar_x - w as i32, bar_y + along),
+        };
+        (
+            x.min(screen_x + screen_w as i32 - w as i32).max(screen_x),
+            y.min(screen_y + screen_h as i32 - h as i32).max(screen_y),
+        )
+    }
+
+    /// The position of a window of size `w` by `h` at the far end of the bar, moved `offset`
+    /// pixels away from it to stack windows like notifications
+    pub fn stack_pos(
+        &self,
+        bar_rect: Rect,
+        w: u32,
+        h: u32,
+        offset: i32,
+        screen: Rect,
+    ) -> (i32, i32) {
+        let (_, _, bar_w, bar_h) = bar_rect;
+        let (bar_len, _) = self.along_across(bar_w as i32, bar_h as i32);
+        let (len, _) = self.along_across(w as i32, h as i32);
+        let along = if self.vertical() {
+            bar_len - len - offset
+        } else {
+            bar_len - len
+        };
+        let (x, y) = self.popup_pos(bar_rect, along, w, h, screen);
+        match self.edge {
+            Edge::Top => (x, y + offset),
+            Edge::Bottom => (x, y - offset),
+            Edge::Left | Edge::Right => (x, y),
+        }
+    }
+
+    /// The x coordinate of a window `w` pixels wide beside a menu, on the side away from the
+    /// screen edge
+    pub fn beside_x(&self, menu_x: i32, menu_w: u32, w: u32) -> i32 {
+        if self.edge == Edge::Right {
+            menu_x - w as i32
+        } else {
+            menu_x + menu_w as i32
+        }
+    }
+
+    /// The rectangle of the indicator of a running package, on the side of its icon facing away
+    /// from the screen edge
+    pub fn indicator_rect(&self, x: i32, y: i32, w: u32, h: u32) -> Rect {
+        match self.edge {
+            Edge::Top => (x + 4, y + h as i32 - 2, w.saturating_sub(8), 2),
+            Edge::Bottom => (x + 4, y, w.saturating_sub(8), 2),
+            Edge::Left => (x + w as i32 - 2, y + 4, 2, h.saturating_sub(8)),
+            Edge::Right => (x, y + 4, 2, h.saturating_sub(8)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = (0, 0, 800, 600);
+
+    fn placement(edge: Edge) -> Placement {
+        Placement {
+            edge,
+            auto_hide: false,
+            displays: Displays::Primary(0),
+        }
+    }
+
+    #[test]
+    fn bar_rects() {
+        assert_eq!(
+            placement(Edge::Bottom).bar_rect(SCREEN, 48),
+            (0, 552, 800, 48)
+        );
+        assert_eq!(placement(Edge::Top).bar_rect(SCREEN, 48), (0, 0, 800, 48));
+        assert_eq!(placement(Edge::Left).bar_rect(SCREEN, 48), (0, 0, 48, 600));
+        assert_eq!(
+            placement(Edge::Right).bar_rect(SCREEN, 48),
+            (752, 0, 48, 600)
+        );
+    }
+
+    #[test]
+    fn hidden_rects() {
+        assert_eq!(
+            placement(Edge::Bottom).hidden_rect(SCREEN, 48),
+            (0, 598, 800, 48)
+        );
+        assert_eq!(
+            placement(Edge::Top).hidden_rect(SCREEN, 48),
+            (0, -46, 800, 48)
+        );
+        assert_eq!(
+            placement(Edge::Left).hidden_rect(SCREEN, 48),
+            (-46, 0, 48, 600)
+        );
+        assert_eq!(
+            placement(Edge::Right).hidden_rect(SCREEN, 48),
+            (798, 0, 48, 600)
+        );
+    }
+
+    #[test]
+    fn popups() {
+        let pos = |edge, along, w, h| {
+            let placement = placement(edge);
+            let bar_rect = placement.bar_rect(SCREEN, 48);
+            placement.popup_pos(bar_rect, along, w, h, SCREEN)
+        };
+        assert_eq!(pos(Edge::Bottom, 48, 200, 100), (48, 452));
+        assert_eq!(pos(Edge::Top, 48, 200, 100), (48, 48));
+        assert_eq!(pos(Edge::Left, 48, 200, 100), (48, 48));
+        assert_eq!(pos(Edge::Right, 48, 200, 100), (552, 48));
+        // Popups are kept on the screen
+        assert_eq!(pos(Edge::Bottom, 700, 200, 100), (600, 452));
+        assert_eq!(pos(Edge::Left, 550, 200, 100), (48, 500));
+    }
+
+    #[test]
+    fn stacks() {
+        let pos = |edge, offset| {
+            let placement = placement(edge);
+            let bar_rect = placement.bar_rect(SCREEN, 48);
+            placement.stack_pos(bar_rect, 300, 80, offset, SCREEN)
+        };
+        assert_eq!(pos(Edge::Bottom, 0), (500, 472));
+        assert_eq!(pos(Edge::Bottom, 88), (500, 384));
+        assert_eq!(pos(Edge::Top, 88), (500, 136));
+        assert_eq!(pos(Edge::Right, 0), (452, 520));
+        assert_eq!(pos(Edge::Left, 88), (48, 432));
+    }
+
+    #[test]
+    fn second_display() {
+        let placement = placement(Edge::Bottom);
+        let screen = (800, 0, 1024, 768);
+        let bar_rect = placement.bar_rect(screen, 48);
+        assert_eq!(bar_rect, (800, 720, 1024, 48));
+        assert_eq!(placement.hidden_rect(screen, 48), (800, 766, 1024, 48));
+        assert_eq!(
+            placement.popup_pos(bar_rect, 0, 200, 100, screen),
+            (800, 620)
+        );
+        assert_eq!(
+            placement.popup_pos(bar_rect, 1000, 200, 100, screen),
+            (1624, 620)
+        );
+    }
+
+    #[test]
+    fn displays() {
+        let screens = [(0, 0, 800, 600), (800, 0, 1024, 768)];
+        let mut placement = placement(Edge::Bottom);
+        assert_eq!(placement.select_displays(&screens), vec![screens[0]]);
+        placement.displays = Displays::Primary(1);
+        assert_eq!(placement.select_displays(&screens), vec![screens[1]]);
+        placement.displays = Displays::Primary(2);
+        assert_eq!(placement.select_displays(&screens), vec![screens[0]]);
+        placement.displays = Displays::All;
+        assert_eq!(placement.select_displays(&screens), screens.to_vec());
+        assert!(placement.select_displays(&[]).is_empty());
+    }
+
+    #[test]
+    fn coordinates() {
+        let vertical = placement(Edge::Left);
+        assert_eq!(vertical.along_across(10, 20), (20, 10));
+        assert_eq!(vertical.to_xy(20, 10), (10, 20));
+        let horizontal = placement(Edge::Top);
+        assert_eq!(horizontal.along_across(10, 20), (10, 20));
+        assert_eq!(horizontal.to_xy(10, 20), (10, 20));
+    }
+
+    #[test]
+    fn indicators() {
+        assert_eq!(
+            placement(Edge::Bottom).indicator_rect(48, 552, 48, 48),
+            (52, 552, 40, 2)
+        );
+        assert_eq!(
+            placement(Edge::Left).indicator_rect(0, 48, 48, 48),
+            (46, 52, 2, 40)
+        );
+        // Icons that failed to load may be empty
+        assert_eq!(
+            placement(Edge::Top).indicator_rect(0, 0, 0, 0),
+            (4, -2, 0, 2)
+        );
+        assert_eq!(
+            placement(Edge::Right).indicator_rect(0, 0, 4, 4),
+            (0, 4, 2, 0)
+        );
+    }
+}
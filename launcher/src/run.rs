@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use crate::config::data_file;
+
+/// The most commands kept in the run history
+const HISTORY_LEN: usize = 100;
+
+/// A completion of the command line in the run dialog
+#[derive(Clone, Debug, PartialEq)]
+pub enum Completion {
+    /// A command line that was run before
+    History(String),
+    /// A package, by index
+    Package(usize),
+    /// An executable in `PATH`
+    Executable(String),
+}
+
+/// Find the names of the executables in the directories of a `PATH`-style list
+pub fn executables(path_var: &OsStr) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for dir in env::split_paths(path_var) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let executable = entry.metadata().is_ok_and(|metadata| {
+                metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+            });
+            if let (true, Some(name)) = (executable, entry.file_name().to_str()) {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// Complete a command line from the history, newest first, then package names and executables
+/// starting with it
+///
+/// Only history is searched once the command line has arguments.
+pub fn complete(
+    input: &str,
+    history: &[String],
+    package_names: &[&str],
+    executables: &[String],
+    limit: usize,
+) -> Vec<Completion> {
+    let mut completions = Vec::new();
+    for command in history.iter().rev() {
+        if command.starts_with(input) && command != input {
+            completions.push(Completion::History(command.clone()));
+        }
+    }
+
+    let input_lower = input.to_lowercase();
+    if !input.is_empty() && !input.contains(char::is_whitespace) {
+        for (i, name) in package_names.iter().enumerate() {
+            if name.to_lowercase().starts_with(&input_lower) {
+                completions.push(Completion::Package(i));
+            }
+        }
+        for name in executables {
+            let in_history = history.iter().any(|command| command == name);
+            if name.starts_with(input) && name != input && !in_history {
+                completions.push(Completion::Executable(name.clone()));
+            }
+        }
+    }
+
+    completions.truncate(limit);
+    completions
+}
+
+/// The command lines run from the run dialog, saved in `launcher/run_history` in the data
+/// directory
+pub struct RunHistory {
+    path_opt: Option<PathBuf>,
+    /// The command lines, oldest first
+    pub commands: Vec<String>,
+}
+
+impl RunHistory {
+    pub fn load() -> Self {
+        Self::load_from(data_file("run_history"))
+    }
+
+    fn load_from(path_opt: Option<PathBuf>) -> Self {
+        let commands = path_opt
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|data| {
+                data.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| line.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        RunHistory { path_opt, commands }
+    }
+
+    /// Add a command line as the newest, removing older copies, and save the history
+    pub fn add(&mut self, command: &str) {
+        self.commands.retain(|x| x != command);
+        self.commands.push(command.to_string());
+        if self.commands.len() > HISTORY_LEN {
+            self.commands.drain(..self.commands.len() - HISTORY_LEN);
+        }
+
+        if let Some(path) = &self.path_opt {
+            let mut data = self.commands.join("\n");
+            data.push('\n');
+            if let Err(err) = fs::write(path, data) {
+                log::error!("failed to save run history {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(x: &[&str]) -> Vec<String> {
+        x.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn completions() {
+        let history = strings(&["cosmic-term", "cat /etc/hostname", "calc"]);
+        let executables = strings(&["calc", "cargo", "cat", "cosmic-term", "ls"]);
+        let packages = ["Calculator", "Files"];
+        assert_eq!(
+            complete("ca", &history, &packages, &executables, 10),
+            vec![
+                Completion::History("calc".to_string()),
+                Completion::History("cat /etc/hostname".to_string()),
+                Completion::Package(0),
+                Completion::Executable("cargo".to_string()),
+                Completion::Executable("cat".to_string()),
+            ]
+        );
+        assert_eq!(
+            complete("cat ", &history, &packages, &executables, 10),
+            vec![Completion::History("cat /etc/hostname".to_string())]
+        );
+        assert_eq!(
+            complete("f", &history, &packages, &executables, 10),
+            vec![Completion::Package(1)]
+        );
+        assert_eq!(
+            complete("ca", &history, &packages, &executables, 1).len(),
+            1
+        );
+        // An empty command line lists the history
+        assert_eq!(complete("", &history, &packages, &executables, 10).len(), 3);
+    }
+
+    #[test]
+    fn history() {
+        let dir = env::temp_dir().join(format!("launcher-run-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run_history");
+
+        let mut history = RunHistory::load_from(Some(path.clone()));
+        assert!(history.commands.is_empty());
+        history.add("ls");
+        history.add("cat /etc/hostname");
+        history.add("ls");
+        assert_eq!(history.commands, strings(&["cat /etc/hostname", "ls"]));
+        for i in 0..HISTORY_LEN {
+            history.add(&i.to_string());
+        }
+        assert_eq!(history.commands.len(), HISTORY_LEN);
+        assert_eq!(history.commands[0], "0");
+
+        let loaded = RunHistory::load_from(Some(path));
+        assert_eq!(loaded.commands, history.commands);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn path_executables() {
+        let dir = env::temp_dir().join(format!("launcher-path-{}", std::process::id()));
+        fs::create_dir_all(dir.join("subdir")).unwrap();
+        for (name, mode) in [("tool", 0o755), ("data.txt", 0o644)] {
+            let path = dir.join(name);
+            fs::write(&path, "").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        let path_var = env::join_paths([dir.clone(), dir.join("missing")]).unwrap();
+        assert_eq!(executables(&path_var), vec!["tool"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+
+use crate::package::Package;
+
+/// The most that `boost` can add to the score of a package in `search`
+///
+/// A match in the name scores four times its fuzzy score, so this is less than the 24 points one
+/// more consecutive character of the name adds, and usage only reorders matches of similar
+/// quality.
+pub const MAX_BOOST: i32 = 20;
+
+/// Score how well `query` fuzzy matches `text`, higher is better
+///
+/// Every character of the query must appear in the text in order. Matches at the start of the
+/// text or of a word, and runs of consecutive matches, score higher than scattered ones. A match
+/// scores at least 1, so that gaps never make the weights of `package_score` rank it lower.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut query_i = 0;
+    let mut last_match: Option<usize> = None;
+    for (text_i, c) in text.iter().enumerate() {
+        if query_i >= query.len() {
+            break;
+        }
+        if *c != query[query_i] {
+            continue;
+        }
+
+        score += 1;
+        if text_i == 0 {
+            score += 15;
+        } else if !text[text_i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match last_match {
+            Some(last_i) if last_i + 1 == text_i => score += 5,
+            Some(last_i) => score -= (text_i - last_i - 1).min(5) as i32,
+            None => (),
+        }
+
+        last_match = Some(text_i);
+        query_i += 1;
+    }
+
+    if query_i < query.len() {
+        return None;
+    }
+    if query.len() == text.len() {
+        score += 25;
+    }
+    Some(score.max(1))
+}
+
+/// Score a package against a query, preferring matches in the name over other fields
+pub fn package_score(query: &str, package: &Package) -> Option<i32> {
+    let mut best = fuzzy_score(query, &package.name).map(|score| score * 4);
+    let mut consider = |score: Option<i32>, weight: i32| {
+        if let Some(score) = score {
+            best = Some(best.map_or(score * weight, |best| best.max(score * weight)));
+        }
+    };
+    for keyword in package.keywords.iter() {
+        consider(fuzzy_score(query, keyword), 2);
+    }
+    for category in package.categories.iter() {
+        consider(fuzzy_score(query, category), 1);
+    }
+    for text in [&package.generic_name, &package.comment]
+        .into_iter()
+        .flatten()
+    {
+        consider(fuzzy_score(query, text), 1);
+    }
+    for description in package.descriptions.iter() {
+        consider(fuzzy_score(query, description), 1);
+    }
+    best
+}
+
+/// Find the packages matching a query, returning their indexes with the best match first
+///
+/// `boost` is added to the score of every matching package, to rank packages that are used more
+/// higher. It is clamped between 0 and `MAX_BOOST`.
+pub fn search(packages: &[Package], query: &str, boost: impl Fn(&Package) -> i32) -> Vec<usize> {
+    let mut results: Vec<(i32, usize)> = packages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, package)| {
+            let score = package_score(query, package)? + boost(package).clamp(0, MAX_BOOST);
+            Some((score, i))
+        })
+        .collect();
+    results.sort_by(|a, b| match b.0.cmp(&a.0) {
+        Ordering::Equal => packages[a.1].name.cmp(&packages[b.1].name),
+        ordering => ordering,
+    });
+    results.into_iter().map(|(_, i)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str) -> Package {
+        let mut package = Package::new();
+        package.name = name.to_string();
+        package
+    }
+
+    fn names(packages: &[Package], query: &str) -> Vec<String> {
+        search(packages, query, |_| 0)
+            .into_iter()
+            .map(|i| packages[i].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_scores() {
+        assert_eq!(fuzzy_score("", "Files"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "Files"), None);
+        // Characters must be in order
+        assert_eq!(fuzzy_score("sf", "Files"), None);
+        assert_eq!(fuzzy_score("FILES", "files"), fuzzy_score("files", "Files"));
+
+        let score = |query, text| fuzzy_score(query, text).unwrap();
+        // Prefix matches beat matches inside the text
+        assert!(score("ed", "Editor") > score("ed", "Red"));
+        // Word starts beat other characters
+        assert!(score("fm", "File Manager") > score("fm", "Firmware"));
+        // Consecutive matches beat gaps
+        assert!(score("fi", "Files") > score("fi", "Foil"));
+        // Whole matches beat prefixes
+        assert!(score("files", "Files") > score("files", "Files Plus"));
+        // Gaps do not make a match score below 1
+        assert_eq!(score("ed", "Text Editor"), 1);
+    }
+
+    #[test]
+    fn field_weights() {
+        let name = package("Terminal");
+        let mut keyword = package("Console");
+        keyword.keywords.push("Terminal".to_string());
+        let mut comment = package("Shell");
+        comment.comment = Some("Terminal".to_string());
+        let mut category = package("Rxvt");
+        category.categories.insert("Terminal".to_string());
+
+        let score = |package| package_score("term", package).unwrap();
+        assert_eq!(score(&name), 2 * score(&keyword));
+        assert_eq!(score(&keyword), 2 * score(&comment));
+        assert_eq!(score(&comment), score(&category));
+        assert_eq!(package_score("term", &package("Files")), None);
+
+        let packages = vec![comment, package("Files"), keyword, name];
+        assert_eq!(names(&packages, "term"), ["Terminal", "Console", "Shell"]);
+    }
+
+    #[test]
+    fn ties() {
+        let packages = vec![
+            package("Text Editor"),
+            package("Terminal"),
+            package("Files"),
+        ];
+        assert_eq!(
+            fuzzy_score("te", "Text Editor"),
+            fuzzy_score("te", "Terminal")
+        );
+        assert_eq!(names(&packages, "te"), ["Terminal", "Text Editor"]);
+        assert!(names(&packages, "xyz").is_empty());
+    }
+
+    #[test]
+    fn boosts() {
+        let packages = vec![
+            package("Notepad"),
+            package("Terminal"),
+            package("Text Editor"),
+        ];
+        let ranked = |used: &str, boost: i32| -> Vec<String> {
+            search(&packages, "te", |package| {
+                if package.name == used {
+                    boost
+                } else {
+                    0
+                }
+            })
+            .into_iter()
+            .map(|i| packages[i].name.clone())
+            .collect()
+        };
+        assert_eq!(ranked("", 0), ["Terminal", "Text Editor", "Notepad"]);
+        // Usage reorders matches of the same quality
+        assert_eq!(
+            ranked("Text Editor", 1),
+            ["Text Editor", "Terminal", "Notepad"]
+        );
+        // But does not lift a weak match above a better match in the name
+        let weak = package_score("te", &package("Notepad")).unwrap();
+        assert!(weak + MAX_BOOST < package_score("te", &package("Terminal")).unwrap());
+        assert_eq!(
+            ranked("Notepad", 1000),
+            ["Terminal", "Text Editor", "Notepad"]
+        );
+        assert_eq!(
+            ranked("Notepad", -1000),
+            ["Terminal", "Text Editor", "Notepad"]
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+use orbutils_shared::notification::Notification;
+
+use crate::config::config_lines;
+use crate::exec::exec_to_command;
+use crate::locale::translate;
+
+/// The config file holding session service settings
+const CONFIG_NAME: &str = "services";
+
+/// The services started when none are configured
+///
+/// Notifications are received by the launcher itself, so no notification daemon is needed by
+/// default.
+const DEFAULT_SERVICES: &[&str] = &["background"];
+
+/// How many times a service is restarted in a row before it is given up
+const DEFAULT_LIMIT: u32 = 5;
+
+/// The delay before the first restart, doubled for every restart in a row after it
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest delay before a restart
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How long a service has to run for its restarts to no longer count as in a row
+const STABLE_TIME: Duration = Duration::from_secs(60);
+
+/// When a service is restarted after it exits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Restart {
+    Always,
+    /// Only when it exits with an error, or fails to start
+    OnFailure,
+    Never,
+}
+
+impl Restart {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "always" => Some(Restart::Always),
+            "on-failure" => Some(Restart::OnFailure),
+            "never" => Some(Restart::Never),
+            _ => None,
+        }
+    }
+}
+
+/// The settings of a service, from the `<name>_exec`, `<name>_restart` and `<name>_limit` keys
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceConfig {
+    pub name: String,
+    /// An exec string, like the Exec key of a desktop entry, the name by default
+    pub exec: String,
+    pub restart: Restart,
+    /// The most restarts in a row before the service is given up
+    pub limit: u32,
+}
+
+impl ServiceConfig {
+    fn new(name: &str) -> Self {
+        ServiceConfig {
+            name: name.to_string(),
+            exec: name.to_string(),
+            restart: Restart::OnFailure,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// Parse the lines of the services config, in the order of the `services` key
+pub fn parse_services(lines: Vec<(String, String)>) -> Vec<ServiceConfig> {
+    let mut names: Vec<String> = DEFAULT_SERVICES.iter().map(|x| x.to_string()).collect();
+    let mut configs = Vec::<ServiceConfig>::new();
+    for (key, value) in lines {
+        if key == "services" {
+            names = value
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+            continue;
+        }
+
+        let Some((name, setting)) = key.rsplit_once('_') else {
+            log::error!("unknown service setting {:?}", key);
+            continue;
+        };
+        let config = match configs.iter_mut().position(|x| x.name == name) {
+            Some(i) => &mut configs[i],
+            None => {
+                configs.push(ServiceConfig::new(name));
+                configs.last_mut().unwrap()
+            }
+        };
+        match setting {
+            "exec" => config.exec = value,
+            "restart" => match Restart::parse(&value) {
+                Some(restart) => config.restart = restart,
+                None => log::error!("invalid service restart policy {:?}", value),
+            },
+            "limit" => match value.parse::<u32>() {
+                Ok(limit) => config.limit = limit,
+                Err(_) => log::error!("invalid service restart limit {:?}", value),
+            },
+            _ => log::error!("unknown service setting {:?}", key),
+        }
+    }
+
+    names
+        .iter()
+        .map(|name| match configs.iter().find(|x| &x.name == name) {
+            Some(config) => config.clone(),
+            None => ServiceConfig::new(name),
+        })
+        .collect()
+}
+
+/// What the supervisor does after a service exits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Exited {
+    /// Start it again at a time
+    Restart(Instant),
+    /// Leave it stopped, as its restart policy says
+    Stopped,
+    /// Leave it stopped, because it was restarted too many times in a row
+    GaveUp,
+}
+
+/// A session service and its state
+pub struct Service {
+    pub config: ServiceConfig,
+    child_opt: Option<Child>,
+    started: Option<Instant>,
+    /// The restarts since the service last ran for `STABLE_TIME`
+    restarts: u32,
+    restart_at: Option<Instant>,
+}
+
+impl Service {
+    pub fn new(config: ServiceConfig) -> Self {
+        Service {
+            config,
+            child_opt: None,
+            started: None,
+            restarts: 0,
+            restart_at: None,
+        }
+    }
+
+    /// Decide what to do after the service exits or fails to start
+    pub fn exited(&mut self, success: bool, now: Instant) -> Exited {
+        self.child_opt = None;
+        let restart = match self.config.restart {
+            Restart::Always => true,
+            Restart::OnFailure => !success,
+            Restart::Never => false,
+        };
+        if !restart {
+            return Exited::Stopped;
+        }
+
+        let stable = self
+            .started
+            .is_some_and(|started| now.duration_since(started) >= STABLE_TIME);
+        if stable {
+            self.restarts = 0;
+        }
+        if self.restarts >= self.config.limit {
+            return Exited::GaveUp;
+        }
+
+        let backoff = BASE_BACKOFF
+            .saturating_mul(1 << self.restarts.min(16))
+            .min(MAX_BACKOFF);
+        self.restarts += 1;
+        let restart_at = now + backoff;
+        self.restart_at = Some(restart_at);
+        Exited::Restart(restart_at)
+    }
+
+    /// Start the service, returns what to do if it fails to start
+    fn start(&mut self, now: Instant) -> Option<Exited> {
+        self.restart_at = None;
+        self.started = Some(now);
+        let Some(mut command) = exec_to_command(&self.config.exec, None, &[]) else {
+            log::error!("failed to parse {}", self.config.exec);
+            return Some(self.exited(false, now));
+        };
+        match command.spawn() {
+            Ok(child) => {
+                log::info!("started {} ({})", self.config.name, child.id());
+                self.child_opt = Some(child);
+                None
+            }
+            Err(err) => {
+                log::error!("failed to launch {}: {}", self.config.name, err);
+                Some(self.exited(false, now))
+            }
+        }
+    }
+
+    /// Check whether the service exited, returns what to do if it did
+    fn check(&mut self, now: Instant) -> Option<Exited> {
+        let child = self.child_opt.as_mut()?;
+        match child.try_wait() {
+            Ok(None) => None,
+            Ok(Some(status)) => {
+                log::info!(
+                    "{} ({}) exited with {}",
+                    self.config.name,
+                    child.id(),
+                    status
+                );
+                Some(self.exited(status.success(), now))
+            }
+            Err(err) => {
+                log::error!("failed to wait for {}: {}", self.config.name, err);
+                Some(self.exited(false, now))
+            }
+        }
+    }
+}
+
+/// The session services started and restarted by the launcher, configured in
+/// `launcher/services`
+pub struct Services {
+    services: Vec<Service>,
+}
+
+impl Services {
+    pub fn new() -> Self {
+        Services {
+            services: parse_services(config_lines(CONFIG_NAME))
+                .into_iter()
+                .map(Service::new)
+                .collect(),
+        }
+    }
+
+    /// Start every service, returns notifications for the services that were given up
+    pub fn start(&mut self, now: Instant) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        for service in self.services.iter_mut() {
+            if let Some(exited) = service.start(now) {
+                notifications.extend(given_up(service, exited));
+            }
+        }
+        notifications
+    }
+
+    /// Restart services that exited and are due, returns notifications for the services that
+    /// were given up
+    pub fn supervise(&mut self, now: Instant) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        for service in self.services.iter_mut() {
+            let mut exited_opt = service.check(now);
+            if let Exited::Restart(restart_at) = exited_opt.unwrap_or(Exited::Stopped) {
+                log::info!(
+                    "restarting {} in {:?}",
+                    service.config.name,
+                    restart_at.duration_since(now)
+                );
+            }
+            if service
+                .restart_at
+                .is_some_and(|restart_at| now >= restart_at)
+            {
+                exited_opt = service.start(now);
+            }
+            if let Some(exited) = exited_opt {
+                notifications.extend(given_up(service, exited));
+            }
+        }
+        notifications
+    }
+
+    /// Take the running services, to end them with the other children when the session ends
+    pub fn take_children(&mut self) -> Vec<(String, Child)> {
+        self.services
+            .iter_mut()
+            .filter_map(|service| {
+                let child = service.child_opt.take()?;
+                Some((service.config.name.clone(), child))
+            })
+            .collect()
+    }
+}
+
+/// Get the notification telling the user that a service was given up
+fn given_up(service: &Service, exited: Exited) -> Option<Notification> {
+    if exited != Exited::GaveUp {
+        return None;
+    }
+    log::error!(
+        "{} was restarted {} times in a row, giving up",
+        service.config.name,
+        service.restarts
+    );
+    Some(Notification {
+        app_name: "launcher".to_string(),
+        summary: translate("Session service stopped"),
+        body: service.config.name.clone(),
+        icon: "dialog-warning".to_string(),
+        // Shown until it is clicked, as the service stays stopped
+        timeout: Some(Duration::ZERO),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::lines;
+
+    #[test]
+    fn configs() {
+        assert_eq!(
+            parse_services(Vec::new()),
+            vec![ServiceConfig::new("background")]
+        );
+        let configs = parse_services(lines(&[
+            ("services", "background, clipboard_daemon"),
+            ("background_restart", "always"),
+            ("clipboard_daemon_exec", "clipd --session"),
+            ("clipboard_daemon_limit", "2"),
+            ("clipboard_daemon_restart", "sometimes"),
+            ("unused_exec", "unused"),
+        ]));
+        assert_eq!(
+            configs,
+            vec![
+                ServiceConfig {
+                    restart: Restart::Always,
+                    ..ServiceConfig::new("background")
+                },
+                ServiceConfig {
+                    name: "clipboard_daemon".to_string(),
+                    exec: "clipd --session".to_string(),
+                    restart: Restart::OnFailure,
+                    limit: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn backoff() {
+        let now = Instant::now();
+        let mut service = Service::new(ServiceConfig {
+            limit: 3,
+            ..ServiceConfig::new("background")
+        });
+        assert_eq!(
+            service.exited(false, now),
+            Exited::Restart(now + Duration::from_secs(1))
+        );
+        assert_eq!(
+            service.exited(false, now),
+            Exited::Restart(now + Duration::from_secs(2))
+        );
+        assert_eq!(
+            service.exited(false, now),
+            Exited::Restart(now + Duration::from_secs(4))
+        );
+        assert_eq!(service.exited(false, now), Exited::GaveUp);
+
+        // Running for long enough resets the count
+        service.started = Some(now);
+        let later = now + STABLE_TIME;
+        assert_eq!(
+            service.exited(false, later),
+            Exited::Restart(later + Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn max_backoff() {
+        let now = Instant::now();
+        let mut service = Service::new(ServiceConfig {
+            limit: 100,
+            ..ServiceConfig::new("background")
+        });
+        for _ in 0..50 {
+            service.exited(false, now);
+        }
+        assert_eq!(
+            service.exited(false, now),
+            Exited::Restart(now + MAX_BACKOFF)
+        );
+    }
+
+    #[test]
+    fn supervise() {
+        let mut services = Services {
+            services: vec![
+                Service::new(ServiceConfig::new("true")),
+                Service::new(ServiceConfig::new("false")),
+            ],
+        };
+        let now = Instant::now();
+        assert!(services.start(now).is_empty());
+        for service in services.services.iter_mut() {
+            let child = service.child_opt.as_mut().unwrap();
+            child.wait().unwrap();
+        }
+
+        // A service that exits successfully is not restarted by default, one that fails is
+        assert!(services.supervise(now).is_empty());
+        let [ok, failed] = &services.services[..] else {
+            panic!("services changed");
+        };
+        assert!(ok.child_opt.is_none());
+        assert_eq!(ok.restart_at, None);
+        assert!(failed.child_opt.is_none());
+        assert_eq!(failed.restart_at, Some(now + BASE_BACKOFF));
+
+        let later = now + MAX_BACKOFF;
+        assert!(services.supervise(later).is_empty());
+        assert!(services.services[0].child_opt.is_none());
+        assert!(services.services[1].child_opt.is_some());
+        for (_, mut child) in services.take_children() {
+            child.wait().unwrap();
+        }
+    }
+
+    #[test]
+    fn policies() {
+        let now = Instant::now();
+        let mut service = Service::new(ServiceConfig::new("background"));
+        assert_eq!(service.exited(true, now), Exited::Stopped);
+        service.config.restart = Restart::Always;
+        assert!(matches!(service.exited(true, now), Exited::Restart(_)));
+        service.config.restart = Restart::Never;
+        assert_eq!(service.exited(false, now), Exited::Stopped);
+    }
+}
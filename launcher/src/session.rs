@@ -0,0 +1,207 @@
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::config_lines;
+
+/// The config file holding session settings
+const CONFIG_NAME: &str = "session";
+
+/// How long applications are given to quit when no grace period is configured
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// How often applications are checked while waiting for them to quit
+const POLL_TIME: Duration = Duration::from_millis(50);
+
+/// How the session ends
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionEnd {
+    Logout,
+    Restart,
+    Shutdown,
+}
+
+impl SessionEnd {
+    /// Parse the value of a `session=` exec string from the start menu
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "logout" => Some(SessionEnd::Logout),
+            "restart" => Some(SessionEnd::Restart),
+            "shutdown" => Some(SessionEnd::Shutdown),
+            _ => None,
+        }
+    }
+
+    /// The value of the `session=` exec string
+    pub fn name(self) -> &'static str {
+        match self {
+            SessionEnd::Logout => "logout",
+            SessionEnd::Restart => "restart",
+            SessionEnd::Shutdown => "shutdown",
+        }
+    }
+
+    /// The untranslated label of the start menu entry
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionEnd::Logout => "Logout",
+            SessionEnd::Restart => "Restart",
+            SessionEnd::Shutdown => "Shutdown",
+        }
+    }
+
+    /// The name of the icon of the start menu entry, in the actions directory of the UI icons
+    pub fn icon(self) -> &'static str {
+        match self {
+            SessionEnd::Logout => "system-log-out",
+            SessionEnd::Restart => "system-reboot",
+            SessionEnd::Shutdown => "system-shutdown",
+        }
+    }
+
+    /// The command run after the session ends, the same as the power menu of orblogin
+    pub fn command(self) -> Option<Command> {
+        match self {
+            SessionEnd::Logout => None,
+            SessionEnd::Restart => {
+                let mut command = Command::new("shutdown");
+                command.arg("-r");
+                Some(command)
+            }
+            SessionEnd::Shutdown => Some(Command::new("shutdown")),
+        }
+    }
+}
+
+/// Parse the lines of the session config, returns the grace period
+pub fn parse_grace_period(lines: Vec<(String, String)>) -> Duration {
+    let mut grace_period = DEFAULT_GRACE_PERIOD;
+    for (key, value) in lines {
+        match key.as_str() {
+            "grace_period" => match value.parse::<u64>() {
+                Ok(secs) => grace_period = Duration::from_secs(secs),
+                Err(_) => log::error!("invalid session grace period {:?}", value),
+            },
+            _ => log::error!("unknown session setting {:?}", key),
+        }
+    }
+    grace_period
+}
+
+/// How long applications are given to quit before they are killed, from the `grace_period` key
+/// in seconds
+pub fn grace_period() -> Duration {
+    parse_grace_period(config_lines(CONFIG_NAME))
+}
+
+/// Ask every child to quit with `SIGTERM`, wait up to the grace period for them to exit, then
+/// kill the rest
+pub fn terminate(mut children: Vec<(String, Child)>, grace_period: Duration) {
+    for (exec, child) in children.iter() {
+        let pid = child.id();
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+            log::error!(
+                "failed to ask {} ({}) to quit: {}",
+                exec,
+                pid,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    let deadline = Instant::now() + grace_period;
+    loop {
+        children.retain_mut(|(exec, child)| match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                log::info!("{} ({}) exited with {}", exec, child.id(), status);
+                false
+            }
+            Err(err) => {
+                log::error!("failed to wait for {} ({}): {}", exec, child.id(), err);
+                false
+            }
+        });
+        if children.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(POLL_TIME);
+    }
+
+    for (exec, child) in children.iter_mut() {
+        let pid = child.id();
+        log::warn!("{} ({}) did not quit in time, killing it", exec, pid);
+        if let Err(err) = child.kill() {
+            log::error!("failed to kill {} ({}): {}", exec, pid, err);
+        }
+        match child.wait() {
+            Ok(status) => log::info!("{} ({}) exited with {}", exec, pid, status),
+            Err(err) => log::error!("failed to wait for {} ({}): {}", exec, pid, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::lines;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    #[test]
+    fn grace_periods() {
+        assert_eq!(parse_grace_period(Vec::new()), DEFAULT_GRACE_PERIOD);
+        assert_eq!(
+            parse_grace_period(lines(&[("grace_period", "10")])),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            parse_grace_period(lines(&[("grace_period", "soon")])),
+            DEFAULT_GRACE_PERIOD
+        );
+    }
+
+    #[test]
+    fn session_ends() {
+        for end in [
+            SessionEnd::Logout,
+            SessionEnd::Restart,
+            SessionEnd::Shutdown,
+        ] {
+            assert_eq!(SessionEnd::parse(end.name()), Some(end));
+        }
+        assert!(SessionEnd::Logout.command().is_none());
+        let restart = SessionEnd::Restart.command().unwrap();
+        assert_eq!(restart.get_program(), "shutdown");
+        assert_eq!(restart.get_args().collect::<Vec<_>>(), ["-r"]);
+    }
+
+    #[test]
+    fn terminates() {
+        let spawn = |script: &str| {
+            let child = Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            (script.to_string(), child)
+        };
+        let mut children = vec![
+            spawn("exec sleep 10"),
+            spawn("trap '' TERM; echo ready; exec sleep 10"),
+        ];
+        // Wait for the shell to ignore SIGTERM, which sleep inherits
+        let mut line = String::new();
+        let stdout = children[1].1.stdout.as_mut().unwrap();
+        BufReader::new(stdout).read_line(&mut line).unwrap();
+        assert_eq!(line, "ready\n");
+
+        let started = Instant::now();
+        terminate(children, Duration::from_millis(500));
+        // The child ignoring SIGTERM is killed after the grace period
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(500));
+        assert!(elapsed < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,193 @@
+//! Snapshot tests of the launcher UI, drawn into in-memory images and compared with the golden
+//! PNGs in `tests/fixtures/snapshots`
+//!
+//! After an intended change to the UI, run the tests with `UPDATE_SNAPSHOTS=1` to rewrite the
+//! golden PNGs, and check the new images before committing them.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use orbclient::image::Image;
+use orbclient::{Color, Renderer};
+use orbfont::Font;
+use resvg::tiny_skia::{ColorU8, IntSize, Pixmap};
+
+use crate::applet::{AppletConfig, Applets, MemoryApplet};
+use crate::package::{IconSource, Package};
+use crate::placement::{Displays, Edge, Placement};
+use crate::{
+    chooser_width, draw_bar, draw_chooser, draw_search, icon_size, icon_small_size, BarContents,
+};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Load the font the snapshots are drawn with, so they do not depend on the installed fonts
+fn font() -> Font {
+    Font::from_path(fixtures().join("fonts/Tuffy.ttf")).unwrap()
+}
+
+/// Create a package with an icon from the fixtures, or with no icon
+fn package(name: &str, exec: &str, icon: Option<&str>) -> Package {
+    let mut package = Package::new();
+    package.id = format!("{}.desktop", exec);
+    package.name = name.to_string();
+    package.exec = exec.to_string();
+    if let Some(icon) = icon {
+        let path = fixtures().join("icons").join(format!("{}.svg", icon));
+        package.icon.source = IconSource::Path(path.clone());
+        package.icon_small.source = IconSource::Path(path);
+    }
+    package
+}
+
+fn apps() -> Vec<Package> {
+    vec![
+        package("Terminal", "terminal", Some("terminal")),
+        package("Text Editor", "editor", Some("editor")),
+        package("Files", "files", Some("files")),
+    ]
+}
+
+/// Encode an image as a PNG
+fn encode_png(image: &Image) -> Vec<u8> {
+    let mut data = Vec::with_capacity(image.data().len() * 4);
+    for color in image.data() {
+        let color = ColorU8::from_rgba(color.r(), color.g(), color.b(), color.a()).premultiply();
+        data.extend([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    let size = IntSize::from_wh(image.width(), image.height()).unwrap();
+    Pixmap::from_vec(data, size).unwrap().encode_png().unwrap()
+}
+
+/// Compare an image with the golden PNG of a snapshot, or write the PNG with `UPDATE_SNAPSHOTS`
+///
+/// On a mismatch, the image is written to the temporary directory to compare by hand.
+fn assert_snapshot(name: &str, image: &Image) {
+    let png = encode_png(image);
+    let golden_path = fixtures().join("snapshots").join(format!("{}.png", name));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&golden_path, &png).unwrap();
+        return;
+    }
+
+    let golden_data = fs::read(&golden_path).unwrap_or_else(|err| {
+        panic!(
+            "failed to read {}: {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            golden_path.display(),
+            err
+        )
+    });
+    // Both are decoded, so that they are compared with the same precision
+    let golden = Pixmap::decode_png(&golden_data).unwrap();
+    let actual = Pixmap::decode_png(&png).unwrap();
+    if (golden.width(), golden.height(), golden.data())
+        == (actual.width(), actual.height(), actual.data())
+    {
+        return;
+    }
+
+    let actual_path = env::temp_dir().join(format!("launcher-snapshot-{}.png", name));
+    fs::write(&actual_path, &png).unwrap();
+    panic!(
+        "{} does not match {}, the new image is in {}",
+        name,
+        golden_path.display(),
+        actual_path.display()
+    );
+}
+
+#[test]
+fn bar() {
+    let font = font();
+    let screen = (0, 0, 640, 480);
+    let placement = Placement {
+        edge: Edge::Bottom,
+        auto_hide: false,
+        displays: Displays::Primary(0),
+    };
+    let start = Image::from_color(
+        icon_size() as u32,
+        icon_size() as u32,
+        Color::rgb(0x34, 0x65, 0xa4),
+    );
+    let mut packages = apps();
+    let mut applets = Applets::from_applets(vec![Box::new(MemoryApplet::new(AppletConfig {
+        path: Some(fixtures().join("applets/meminfo")),
+        ..AppletConfig::default()
+    }))]);
+    applets.update(Instant::now());
+
+    let (_, _, w, h) = placement.bar_rect(screen, icon_size() as u32);
+    let mut image = Image::new(w, h);
+    let layout = draw_bar(
+        &mut image,
+        BarContents {
+            start: Some(&start),
+            packages: &mut packages,
+            running: &["editor"],
+            font: &font,
+            placement: &placement,
+            applets: &applets,
+            time: "12:34",
+            date: Some("2026-10-18"),
+        },
+        screen,
+        1,
+    );
+    assert_eq!(layout.selected_package, Some((0, icon_size())));
+    assert_eq!(layout.applet_spans.len(), 1);
+    assert_snapshot("bar", &image);
+}
+
+#[test]
+fn start_menu() {
+    let font = font();
+    // Section headers have no exec
+    let mut header = Package::new();
+    header.name = "Recent".to_string();
+    let mut packages = vec![header];
+    packages.extend(apps().into_iter().take(2));
+    for category in ["Development", "Games", "System"] {
+        packages.push(package(category, &format!("category={}", category), None));
+    }
+    packages.push(package("Logout", "session=logout", None));
+
+    let mut image = Image::new(
+        chooser_width(),
+        packages.len() as u32 * icon_small_size() as u32,
+    );
+    draw_chooser(&mut image, &font, &mut packages, 2);
+    assert_snapshot("start_menu", &image);
+}
+
+#[test]
+fn search() {
+    let font = font();
+    let mut results = apps();
+    results.truncate(2);
+
+    let mut image = Image::new(
+        chooser_width(),
+        (results.len() as u32 + 1) * icon_small_size() as u32,
+    );
+    draw_search(&mut image, &font, "te", &mut results, 0);
+    assert_snapshot("search", &image);
+}
+
+#[test]
+fn chooser() {
+    let font = font();
+    let mut packages = apps();
+    packages.push(package("[x] Always use this application", "always", None));
+
+    let mut image = Image::new(
+        chooser_width(),
+        packages.len() as u32 * icon_small_size() as u32,
+    );
+    draw_chooser(&mut image, &font, &mut packages, -1);
+    assert_snapshot("chooser", &image);
+}
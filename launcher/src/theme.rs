@@ -2,5 +2,6 @@ use orbclient::Color;
 
 pub const BAR_COLOR: Color = Color::rgba(0x1B, 0x1B, 0x1B, 224);
 pub const BAR_HIGHLIGHT_COLOR: Color = Color::rgba(0x36, 0x36, 0x36, 224);
+pub const FOCUS_COLOR: Color = Color::rgb(0x94, 0x94, 0x94);
 pub const TEXT_COLOR: Color = Color::rgb(0xE7, 0xE7, 0xE7);
 pub const TEXT_HIGHLIGHT_COLOR: Color = Color::rgb(0xE7, 0xE7, 0xE7);
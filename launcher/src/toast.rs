@@ -0,0 +1,141 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use orbclient::{Renderer, Window, WindowFlag};
+use orbfont::Font;
+use orbutils_shared::notification::Notification;
+
+use crate::icon_cache;
+use crate::package::IconSource;
+use crate::theme::{BAR_COLOR, TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
+use crate::tooltip::wrap;
+
+/// How long notifications without a timeout are shown
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most toasts shown at once, the oldest are closed first
+pub const MAX_TOASTS: usize = 4;
+
+/// The space between toasts, and between toasts and the bar
+pub const TOAST_GAP: i32 = 8;
+
+/// Get the icon of a notification, which is a path if it has a slash or else an icon name
+pub fn icon_source(icon: &str) -> IconSource {
+    if icon.is_empty() {
+        IconSource::None
+    } else if icon.contains('/') {
+        IconSource::Path(Path::new(icon).to_path_buf())
+    } else {
+        IconSource::Name(icon.to_string())
+    }
+}
+
+/// Get the time a toast closes, None if it stays open until it is clicked
+pub fn expires(notification: &Notification, now: Instant) -> Option<Instant> {
+    match notification.timeout {
+        Some(timeout) if timeout.is_zero() => None,
+        Some(timeout) => Some(now + timeout),
+        None => Some(now + DEFAULT_TIMEOUT),
+    }
+}
+
+/// A notification shown in a window next to the bar until it expires or is clicked
+pub struct Toast {
+    pub window: Window,
+    expires: Option<Instant>,
+}
+
+impl Toast {
+    /// Lay out and draw a notification in a new window `width` pixels wide, which is moved into
+    /// place by the bar
+    pub fn new(
+        notification: &Notification,
+        font: &Font,
+        font_size: i32,
+        width: u32,
+        now: Instant,
+    ) -> Option<Self> {
+        let icon = icon_cache::icon(&icon_source(&notification.icon), true);
+        let text_x = if icon.width() > 0 {
+            8 + icon.width() as i32 + 8
+        } else {
+            8
+        };
+        let text_width = width.saturating_sub(text_x as u32 + 8);
+        let measure = |text: &str| font.render(text, font_size as f32).width();
+
+        let mut lines = Vec::new();
+        for line in wrap(&notification.summary, text_width, measure) {
+            lines.push((font.render(&line, font_size as f32), TEXT_HIGHLIGHT_COLOR));
+        }
+        for paragraph in notification.body.lines() {
+            for line in wrap(paragraph, text_width, measure) {
+                lines.push((font.render(&line, font_size as f32), TEXT_COLOR));
+            }
+        }
+        let text_h: u32 = lines.iter().map(|(text, _)| text.height()).sum();
+        let height = text_h.max(icon.height()) + 16;
+
+        let mut window = Window::new_flags(
+            0,
+            0,
+            width,
+            height,
+            "",
+            &[
+                WindowFlag::Async,
+                WindowFlag::Borderless,
+                WindowFlag::Transparent,
+            ],
+        )?;
+        window.set(BAR_COLOR);
+        window.image(8, 8, icon.width(), icon.height(), icon.data());
+        let mut y = 8;
+        for (text, color) in lines {
+            text.draw(&mut window, text_x, y, color);
+            y += text.height() as i32;
+        }
+        window.sync();
+
+        Some(Toast {
+            window,
+            expires: expires(notification, now),
+        })
+    }
+
+    pub fn expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|expires| now >= expires)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icons() {
+        assert_eq!(icon_source(""), IconSource::None);
+        assert_eq!(
+            icon_source("/usr/share/icons/backup.png"),
+            IconSource::Path("/usr/share/icons/backup.png".into())
+        );
+        assert_eq!(
+            icon_source("dialog-information"),
+            IconSource::Name("dialog-information".to_string())
+        );
+    }
+
+    #[test]
+    fn timeouts() {
+        let now = Instant::now();
+        let mut notification = Notification::default();
+        assert_eq!(expires(&notification, now), Some(now + DEFAULT_TIMEOUT));
+        notification.timeout = Some(Duration::from_millis(1500));
+        assert_eq!(
+            expires(&notification, now),
+            Some(now + Duration::from_millis(1500))
+        );
+        notification.timeout = Some(Duration::ZERO);
+        assert_eq!(expires(&notification, now), None);
+    }
+}
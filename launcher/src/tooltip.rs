@@ -0,0 +1,165 @@
+use orbclient::Renderer;
+use orbfont::Font;
+
+use crate::locale::translate;
+use crate::package::Package;
+use crate::theme::{BAR_COLOR, TEXT_COLOR, TEXT_HIGHLIGHT_COLOR};
+
+/// Get the details shown below the name of a package in its tooltip, one paragraph each
+pub fn tooltip_details(package: &Package) -> Vec<String> {
+    let mut details: Vec<String> = Vec::new();
+    let texts = package
+        .generic_name
+        .iter()
+        .chain(package.comment.iter())
+        .chain(package.descriptions.iter());
+    for text in texts {
+        // Entries often repeat the name or generic name in the comment
+        if !text.is_empty() && *text != package.name && !details.contains(text) {
+            details.push(text.clone());
+        }
+    }
+    if !package.authors.is_empty() {
+        details.push(format!(
+            "{}: {}",
+            translate("Author"),
+            package.authors.join(", ")
+        ));
+    }
+    details
+}
+
+/// Split text into lines no wider than `max_width`, breaking between words when possible
+pub fn wrap(text: &str, max_width: u32, measure: impl Fn(&str) -> u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if measure(&candidate) <= max_width {
+            line = candidate;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        // Words that are too long on their own are broken between characters
+        line = String::new();
+        for c in word.chars() {
+            line.push(c);
+            if line.chars().count() > 1 && measure(&line) > max_width {
+                line.pop();
+                lines.push(line);
+                line = c.to_string();
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// A multi-line tooltip with the name and details of a package
+pub struct Tooltip {
+    /// The lines of the tooltip, the first is the name of the package
+    lines: Vec<String>,
+    font_size: f32,
+}
+
+impl Tooltip {
+    /// Lay out the tooltip of a package, wrapping the lines to `max_width` pixels
+    pub fn new(font: &Font, package: &Package, font_size: i32, max_width: u32) -> Self {
+        let font_size = font_size as f32;
+        let text_width = max_width.saturating_sub(8);
+        let measure = |text: &str| font.render(text, font_size).width();
+
+        let mut lines = wrap(&package.name, text_width, measure);
+        for detail in tooltip_details(package) {
+            lines.extend(wrap(&detail, text_width, measure));
+        }
+        Tooltip { lines, font_size }
+    }
+
+    /// True if the tooltip has details beyond the name of the package
+    pub fn has_details(&self) -> bool {
+        self.lines.len() > 1
+    }
+
+    /// The size of the tooltip, including its padding
+    pub fn size(&self, font: &Font) -> (u32, u32) {
+        let mut w = 0;
+        let mut h = 0;
+        for line in self.lines.iter() {
+            let text = font.render(line, self.font_size);
+            w = w.max(text.width());
+            h += text.height();
+        }
+        (w + 8, h + 8)
+    }
+
+    /// Draw the tooltip with its top left corner at `x`, `y`
+    pub fn draw<R: Renderer>(&self, renderer: &mut R, font: &Font, x: i32, y: i32) {
+        let (w, h) = self.size(font);
+        renderer.rect(x, y, w, h, BAR_COLOR);
+
+        let mut line_y = y + 4;
+        for (i, line) in self.lines.iter().enumerate() {
+            let text = font.render(line, self.font_size);
+            let color = if i == 0 {
+                TEXT_HIGHLIGHT_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            text.draw(renderer, x + 4, line_y, color);
+            line_y += text.height() as i32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure(text: &str) -> u32 {
+        text.chars().count() as u32
+    }
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10, measure),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("  spaced   out  ", 20, measure), vec!["spaced out"]);
+        assert!(wrap("", 10, measure).is_empty());
+    }
+
+    #[test]
+    fn wrap_long_word() {
+        assert_eq!(
+            wrap("a supercalifragilistic word", 8, measure),
+            vec!["a", "supercal", "ifragili", "stic", "word"]
+        );
+    }
+
+    #[test]
+    fn details() {
+        let mut package = Package::new();
+        package.name = "Files".to_string();
+        package.generic_name = Some("File Manager".to_string());
+        package.comment = Some("File Manager".to_string());
+        package.descriptions = vec!["Browse the file system".to_string()];
+        package.authors = vec!["Alice".to_string(), "Bob".to_string()];
+        let details = tooltip_details(&package);
+        assert_eq!(details.len(), 3);
+        assert_eq!(details[0], "File Manager");
+        assert_eq!(details[1], "Browse the file system");
+        assert!(details[2].ends_with("Alice, Bob"));
+    }
+}
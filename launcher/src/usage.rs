@@ -0,0 +1,180 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::data_file;
+use crate::search::MAX_BOOST;
+
+/// Seconds in a day
+const DAY: u64 = 24 * 60 * 60;
+
+/// How often and when a package was last launched
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    pub count: u32,
+    /// The time of the last launch, in seconds since the epoch
+    pub last: u64,
+}
+
+/// Launch counts and times of packages by ID, saved in `launcher/usage` in the data directory
+///
+/// Each line is `id=count,last`.
+pub struct UsageStore {
+    path_opt: Option<PathBuf>,
+    usage: BTreeMap<String, Usage>,
+}
+
+/// Parse the lines of a usage file, skipping invalid lines
+fn parse(data: &str) -> BTreeMap<String, Usage> {
+    let mut usage = BTreeMap::new();
+    for line in data.lines() {
+        let parsed = line.split_once('=').and_then(|(id, value)| {
+            let (count, last) = value.split_once(',')?;
+            Some((
+                id.to_string(),
+                Usage {
+                    count: count.parse().ok()?,
+                    last: last.parse().ok()?,
+                },
+            ))
+        });
+        match parsed {
+            Some((id, package_usage)) => {
+                usage.insert(id, package_usage);
+            }
+            None => log::error!("invalid usage line {:?}", line),
+        }
+    }
+    usage
+}
+
+impl UsageStore {
+    pub fn load() -> Self {
+        Self::load_from(data_file("usage"))
+    }
+
+    fn load_from(path_opt: Option<PathBuf>) -> Self {
+        let usage = path_opt
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|data| parse(&data))
+            .unwrap_or_default();
+        UsageStore { path_opt, usage }
+    }
+
+    /// Record a launch of a package at a time in seconds since the epoch, and save the store
+    pub fn record(&mut self, id: &str, now: u64) {
+        let package_usage = self.usage.entry(id.to_string()).or_default();
+        package_usage.count += 1;
+        package_usage.last = now;
+
+        if let Some(path) = &self.path_opt {
+            let mut data = String::new();
+            for (id, package_usage) in self.usage.iter() {
+                data.push_str(&format!(
+                    "{}={},{}\n",
+                    id, package_usage.count, package_usage.last
+                ));
+            }
+            if let Err(err) = fs::write(path, data) {
+                log::error!("failed to save usage {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// The IDs of the most recently launched packages, most recent first
+    pub fn recent(&self, limit: usize) -> Vec<String> {
+        let mut ids: Vec<(&String, &Usage)> = self.usage.iter().collect();
+        ids.sort_by_key(|(_, usage)| Reverse(usage.last));
+        ids.into_iter()
+            .take(limit)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// The IDs of the most often launched packages, most often first
+    pub fn frequent(&self, limit: usize) -> Vec<String> {
+        let mut ids: Vec<(&String, &Usage)> = self.usage.iter().collect();
+        ids.sort_by_key(|(_, usage)| Reverse((usage.count, usage.last)));
+        ids.into_iter()
+            .take(limit)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Score how much a package is used, counting recent launches more, to rank search results
+    ///
+    /// The score is at most `MAX_BOOST`, reached by 10 launches in the last day.
+    pub fn score(&self, id: &str, now: u64) -> i32 {
+        let Some(package_usage) = self.usage.get(id) else {
+            return 0;
+        };
+        let age = now.saturating_sub(package_usage.last);
+        let weight = if age < DAY {
+            8
+        } else if age < 7 * DAY {
+            4
+        } else if age < 30 * DAY {
+            2
+        } else {
+            1
+        };
+        package_usage.count.min(10) as i32 * weight * MAX_BOOST / 80
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn store() {
+        let dir = env::temp_dir().join(format!("launcher-usage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("usage");
+
+        let mut store = UsageStore::load_from(Some(path.clone()));
+        store.record("files", NOW - 100);
+        store.record("term", NOW - 50);
+        store.record("term", NOW - 40);
+        store.record("editor", NOW - 10);
+        assert_eq!(store.recent(2), vec!["editor", "term"]);
+        assert_eq!(store.frequent(3), vec!["term", "editor", "files"]);
+
+        let loaded = UsageStore::load_from(Some(path));
+        assert_eq!(loaded.usage, store.usage);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_lines() {
+        let usage = parse("term=3,1700000000\nbroken\nfiles=x,1\n");
+        assert_eq!(usage.len(), 1);
+        assert_eq!(
+            usage["term"],
+            Usage {
+                count: 3,
+                last: 1_700_000_000
+            }
+        );
+    }
+
+    #[test]
+    fn scores() {
+        let mut store = UsageStore::load_from(None);
+        assert_eq!(store.score("term", NOW), 0);
+        for _ in 0..3 {
+            store.record("term", NOW - 60);
+        }
+        assert_eq!(store.score("term", NOW), 6);
+        assert_eq!(store.score("term", NOW + 10 * DAY), 1);
+        for _ in 0..20 {
+            store.record("term", NOW);
+        }
+        assert_eq!(store.score("term", NOW), MAX_BOOST);
+    }
+}
@@ -9,11 +9,10 @@ use std::{env, io, str};
 use orbclient::image::Image;
 use orbclient::{Color, EventOption, Renderer, Window, WindowFlag};
 use orbfont::Font;
+use orbutils_shared::keymap::KeymapState;
 use redox_log::{OutputBuilder, RedoxLogger};
 use redox_users::{All, AllUsers, Config};
 
-mod keymap;
-
 #[derive(Clone, Copy)]
 enum BackgroundMode {
     /// Do not resize the image, just center it
@@ -175,7 +174,7 @@ fn login_window(
 
     let mut keymap_dropdown_open = false;
     let mut power_dropdown_open = false;
-    let mut keymap_state = crate::keymap::KeymapState::new();
+    let mut keymap_state = KeymapState::new();
     let keymap_options = keymap_state.list.clone();
     let power_options = vec!["Restart", "Shutdown"];
 
@@ -0,0 +1,104 @@
+use std::env;
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use libredox::flag;
+
+/// The rectangle of a screen in the display
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn get_full_url(path: &str) -> Result<String, String> {
+    let file = match libredox::call::open(path, flag::O_CLOEXEC | flag::O_PATH, 0) {
+        Ok(ok) => unsafe { File::from_raw_fd(ok as RawFd) },
+        Err(err) => return Err(format!("{}", err)),
+    };
+
+    let mut buf: [u8; 4096] = [0; 4096];
+    let count = libredox::call::fpath(file.as_raw_fd() as usize, &mut buf)
+        .map_err(|err| format!("{}", err))?;
+
+    String::from_utf8(Vec::from(&buf[..count])).map_err(|err| format!("{}", err))
+}
+
+/// Parse the scheme, VT and screen, and size of a display from its URL, like
+/// `display.vesa:1.0/1920/1080`
+fn display_size(url: &str) -> Result<(&str, &str, u32, u32), String> {
+    let mut url_parts = url.split(':');
+    let scheme_name = url_parts.next().ok_or("no scheme name")?;
+    let path = url_parts.next().ok_or("no path")?;
+
+    let mut path_parts = path.split('/');
+    let vt_screen = path_parts.next().unwrap_or("");
+    let width = path_parts.next().unwrap_or("").parse::<u32>().unwrap_or(0);
+    let height = path_parts.next().unwrap_or("").parse::<u32>().unwrap_or(0);
+    Ok((scheme_name, vt_screen, width, height))
+}
+
+//TODO: determine x, y of display by talking to orbital instead of guessing!
+/// Get the rectangles of every screen in the VT of `DISPLAY`, placed side by side
+pub fn get_display_rects() -> Result<Vec<DisplayRect>, String> {
+    let url = get_full_url(&env::var("DISPLAY").or(Err("DISPLAY not set"))?)?;
+    let (scheme_name, vt_screen, width, height) = display_size(&url)?;
+
+    let mut display_rects = vec![DisplayRect {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    }];
+
+    // If display server supports multiple displays in a VT
+    if vt_screen.contains('.') {
+        // Look for other screens in the same VT
+        let mut parts = vt_screen.split('.');
+        let vt_i = parts.next().unwrap_or("").parse::<usize>().unwrap_or(0);
+        let start_screen_i = parts.next().unwrap_or("").parse::<usize>().unwrap_or(0);
+        //TODO: determine maximum number of screens
+        for screen_i in start_screen_i + 1..1024 {
+            let url = match get_full_url(&format!("/scheme/{}/{}.{}", scheme_name, vt_i, screen_i))
+            {
+                Ok(ok) => ok,
+                //TODO: only check for ENOENT?
+                Err(_err) => break,
+            };
+            let (_, _, width, height) = display_size(&url)?;
+
+            let x = match display_rects.last() {
+                Some(last) => last.x + last.width as i32,
+                None => 0,
+            };
+            display_rects.push(DisplayRect {
+                x,
+                y: 0,
+                width,
+                height,
+            });
+        }
+    }
+
+    Ok(display_rects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_sizes() {
+        assert_eq!(
+            display_size("display.vesa:1.0/1920/1080"),
+            Ok(("display.vesa", "1.0", 1920, 1080))
+        );
+        assert_eq!(
+            display_size("display.vesa:1"),
+            Ok(("display.vesa", "1", 0, 0))
+        );
+        assert!(display_size("display.vesa").is_err());
+    }
+}
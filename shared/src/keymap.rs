@@ -0,0 +1,122 @@
+use std::process::Command;
+
+/// Parse the output of `inputd --keymaps`, one keymap name per line
+pub fn parse_keymaps(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Get the keymap after the active one, wrapping around to the first
+pub fn next_keymap<'a>(list: &'a [String], active: &str) -> Option<&'a str> {
+    let next_i = match list.iter().position(|x| x == active) {
+        Some(i) => (i + 1) % list.len(),
+        None => 0,
+    };
+    list.get(next_i).map(|x| x.as_str())
+}
+
+/// The keymap assumed to be active, and the only one listed when inputd lists none
+const DEFAULT_KEYMAP: &str = "us";
+
+/// The keymaps known to inputd and the active one
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeymapState {
+    pub active: String,
+    pub list: Vec<String>,
+}
+
+impl KeymapState {
+    pub fn new() -> Self {
+        let list = match Command::new("inputd").arg("--keymaps").output() {
+            Ok(output) => parse_keymaps(&String::from_utf8_lossy(&output.stdout)),
+            Err(err) => {
+                log::warn!("failed to list keymaps: {}", err);
+                Vec::new()
+            }
+        };
+
+        Self::from_list(list)
+    }
+
+    /// Create the state from the listed keymaps, falling back to the default if there are none
+    fn from_list(mut list: Vec<String>) -> Self {
+        if list.is_empty() {
+            list.push(DEFAULT_KEYMAP.to_string());
+        }
+        // TODO: Get active keymap from inputd, until then it is assumed to be the default
+        KeymapState {
+            active: DEFAULT_KEYMAP.to_string(),
+            list,
+        }
+    }
+
+    /// Switch to a keymap with `inputd -K`, returns true if it changed
+    pub fn set_active(&mut self, val: &str) -> bool {
+        if val == self.active {
+            return false;
+        }
+
+        match Command::new("inputd").arg("-K").arg(val).status() {
+            Ok(status) if status.success() => {
+                self.active = val.to_string();
+                true
+            }
+            Ok(status) => {
+                log::error!(
+                    "failed to set keymap {}: inputd exited with {}",
+                    val,
+                    status
+                );
+                false
+            }
+            Err(err) => {
+                log::error!("failed to set keymap {}: {}", val, err);
+                false
+            }
+        }
+    }
+
+    /// Get the keymap after the active one, if any are known
+    pub fn next(&self) -> Option<&str> {
+        next_keymap(&self.list, &self.active)
+    }
+}
+
+impl Default for KeymapState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keymaps() {
+        let list = parse_keymaps("us\ndvorak\n\n  de  \n");
+        assert_eq!(list, vec!["us", "dvorak", "de"]);
+        assert_eq!(next_keymap(&list, "us"), Some("dvorak"));
+        assert_eq!(next_keymap(&list, "de"), Some("us"));
+        assert_eq!(next_keymap(&list, "fr"), Some("us"));
+        assert_eq!(next_keymap(&[], "us"), None);
+    }
+
+    #[test]
+    fn fallback() {
+        assert_eq!(
+            KeymapState::from_list(Vec::new()),
+            KeymapState {
+                active: "us".to_string(),
+                list: vec!["us".to_string()],
+            }
+        );
+        let keymap_state = KeymapState::from_list(parse_keymaps("dvorak\nde\n"));
+        assert_eq!(keymap_state.list, vec!["dvorak", "de"]);
+        assert_eq!(keymap_state.next(), Some("dvorak"));
+    }
+}
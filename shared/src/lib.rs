@@ -0,0 +1,5 @@
+//! Code shared by the Orbital Utilities and the launcher
+
+pub mod display;
+pub mod keymap;
+pub mod notification;
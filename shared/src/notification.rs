@@ -0,0 +1,150 @@
+use std::env;
+use std::io::{self, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A desktop notification, sent by the `notify` client to the launcher
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Notification {
+    /// The name of the program that sent the notification
+    pub app_name: String,
+    pub summary: String,
+    pub body: String,
+    /// An icon name from the icon theme or a path to an icon file, empty for no icon
+    pub icon: String,
+    /// How long the notification is shown, zero to show it until it is clicked, None for the
+    /// default
+    pub timeout: Option<Duration>,
+}
+
+/// Escape backslashes and line breaks so that a value fits on one line
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+impl Notification {
+    /// Serialize as `key=value` lines, with the timeout in milliseconds
+    pub fn encode(&self) -> String {
+        let mut data = String::new();
+        for (key, value) in [
+            ("app", &self.app_name),
+            ("summary", &self.summary),
+            ("body", &self.body),
+            ("icon", &self.icon),
+        ] {
+            if !value.is_empty() {
+                data.push_str(&format!("{}={}\n", key, escape(value)));
+            }
+        }
+        if let Some(timeout) = self.timeout {
+            data.push_str(&format!("timeout={}\n", timeout.as_millis()));
+        }
+        data
+    }
+
+    /// Deserialize from `key=value` lines, a summary is required
+    pub fn decode(data: &str) -> Result<Self, String> {
+        let mut notification = Notification::default();
+        for line in data.lines() {
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("invalid line {:?}", line));
+            };
+            match key {
+                "app" => notification.app_name = unescape(value),
+                "summary" => notification.summary = unescape(value),
+                "body" => notification.body = unescape(value),
+                "icon" => notification.icon = unescape(value),
+                "timeout" => match value.parse::<u64>() {
+                    Ok(ms) => notification.timeout = Some(Duration::from_millis(ms)),
+                    Err(err) => return Err(format!("invalid timeout {:?}: {}", value, err)),
+                },
+                // Newer clients may send more, which are ignored
+                _ => log::debug!("unknown notification key {:?}", key),
+            }
+        }
+        if notification.summary.is_empty() {
+            return Err("no summary".to_string());
+        }
+        Ok(notification)
+    }
+
+    /// Send the notification to the launcher
+    pub fn send(&self) -> io::Result<()> {
+        self.send_to(&socket_path())
+    }
+
+    /// Send the notification to a launcher listening on a socket path
+    pub fn send_to(&self, path: &Path) -> io::Result<()> {
+        let mut stream = UnixStream::connect(path)?;
+        stream.write_all(self.encode().as_bytes())
+    }
+}
+
+/// The path of the socket the launcher receives notifications on, in `XDG_RUNTIME_DIR` or the
+/// temporary directory
+pub fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("launcher-notifications"),
+        None => {
+            let uid = unsafe { libc::getuid() };
+            env::temp_dir().join(format!("launcher-notifications-{}", uid))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let notification = Notification {
+            app_name: "backup".to_string(),
+            summary: "Backup done".to_string(),
+            body: "Copied 3 files\nto C:\\backup".to_string(),
+            icon: "document-save".to_string(),
+            timeout: Some(Duration::from_millis(2500)),
+        };
+        let data = notification.encode();
+        assert_eq!(data.lines().count(), 5);
+        assert_eq!(Notification::decode(&data), Ok(notification));
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            Notification::decode("summary=Hi\nurgency=low\n"),
+            Ok(Notification {
+                summary: "Hi".to_string(),
+                ..Notification::default()
+            })
+        );
+        assert!(Notification::decode("body=No summary\n").is_err());
+        assert!(Notification::decode("summary=Hi\ntimeout=soon\n").is_err());
+        assert!(Notification::decode("summary Hi\n").is_err());
+    }
+}